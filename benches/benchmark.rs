@@ -1,14 +1,23 @@
-#![feature(test)]
-extern crate geohash;
-extern crate test;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use geohash::Precision;
 
-#[bench]
-fn test_iteration(b: &mut test::Bencher) {
+fn test_iteration(c: &mut Criterion) {
     let bounds = geohash::GeohashBits::from_hash("dp3").bounding_box();
-    let mut n = 0;
-    b.iter(|| {
-        let iter = geohash::GeohashIterator::new(bounds, 16);
-        n = iter.count();
+    assert_eq!(geohash::GeohashIterator::new(bounds, 16).count(), 131841);
+    c.bench_function("iteration", |b| {
+        b.iter(|| geohash::GeohashIterator::new(black_box(bounds), 16).count())
+    });
+    // every cell but the last of a row is an East step, which needs to stay
+    // inlined into the iterator
+    let precision = Precision::Characters(6);
+    assert_eq!(
+        geohash::GeohashIterator::with_precision(bounds, precision).count(),
+        33153
+    );
+    c.bench_function("iteration_characters", |b| {
+        b.iter(|| geohash::GeohashIterator::with_precision(black_box(bounds), precision).count())
     });
-    assert_eq!(n, 131841)
 }
+
+criterion_group!(benches, test_iteration);
+criterion_main!(benches);
@@ -0,0 +1,64 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::collections::HashMap;
+
+fn hashes() -> Vec<String> {
+    let bounds = geohash::GeohashBits::from_hash("dp3").bounding_box();
+    geohash::GeohashIterator::with_precision(bounds, geohash::Precision::Characters(8))
+        .take(10_000)
+        .map(|bits| bits.hash())
+        .collect()
+}
+
+// the HashMap lookup the table replaced, for comparison
+fn decode_with_map(map: &HashMap<char, u64>, hash: &str) -> u64 {
+    let total_bit_length = 2 * (5 * hash.len() as u64).div_ceil(2);
+    let mut bits = 0;
+    for (i, c) in hash.chars().enumerate() {
+        bits |= map[&c] << (total_bit_length - 5 * (i as u64 + 1));
+    }
+    bits
+}
+
+fn test_decode(c: &mut Criterion) {
+    let hashes = hashes();
+    let map: HashMap<char, u64> = "0123456789bcdefghjkmnpqrstuvwxyz"
+        .chars()
+        .enumerate()
+        .map(|(i, c)| (c, i as u64))
+        .collect();
+    for hash in &hashes {
+        assert_eq!(
+            decode_with_map(&map, hash),
+            geohash::GeohashBits::from_hash(hash).bits()
+        );
+    }
+
+    let mut group = c.benchmark_group("decode");
+    group.bench_function("hash_map", |b| {
+        b.iter(|| {
+            for hash in &hashes {
+                black_box(decode_with_map(&map, black_box(hash)));
+            }
+        })
+    });
+    group.bench_function("from_hash", |b| {
+        b.iter(|| {
+            for hash in &hashes {
+                black_box(geohash::GeohashBits::from_hash(black_box(hash)));
+            }
+        })
+    });
+    group.bench_function("from_hash_bytes", |b| {
+        b.iter(|| {
+            for hash in &hashes {
+                black_box(geohash::GeohashBits::from_hash_bytes(black_box(
+                    hash.as_bytes(),
+                )));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, test_decode);
+criterion_main!(benches);
@@ -0,0 +1,47 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use geohash::{GeohashBits, Location, Precision};
+
+// BMI2 is used whenever the cpu has a fast `pdep`. On cpus without one, run
+// with and without `--features simd` to compare SSE2 against the scalar version.
+fn test_interleave(c: &mut Criterion) {
+    let locations: Vec<Location> = (0..10_000)
+        .map(|i| Location {
+            longitude: -180.0 + 0.036 * i as f64,
+            latitude: -90.0 + 0.018 * i as f64,
+        })
+        .collect();
+    let cells: Vec<GeohashBits> = locations
+        .iter()
+        .map(|location| GeohashBits::from_location(location, Precision::Bits(26)))
+        .collect();
+
+    let mut group = c.benchmark_group("interleave");
+    group.bench_function("from_location", |b| {
+        b.iter(|| {
+            for location in &locations {
+                black_box(GeohashBits::from_location(
+                    black_box(location),
+                    Precision::Bits(26),
+                ));
+            }
+        })
+    });
+    group.bench_function("encode_batch", |b| {
+        let mut out = vec![0; locations.len()];
+        b.iter(|| {
+            geohash::encode_batch(black_box(&locations), Precision::Bits(26), &mut out).unwrap();
+            black_box(&out);
+        })
+    });
+    group.bench_function("bounding_box", |b| {
+        b.iter(|| {
+            for bits in &cells {
+                black_box(black_box(bits).bounding_box());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, test_interleave);
+criterion_main!(benches);
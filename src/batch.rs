@@ -0,0 +1,266 @@
+// Encoding and decoding of whole slices. Coordinates are converted a chunk at
+// a time and the chunk interleaved in one call, so the interleaving can
+// process several points per instruction. Every input is validated before
+// any output is written.
+use crate::geohash_bits::{bits_to_float, float_to_bits};
+use crate::ops;
+use crate::*;
+
+const CHUNK: usize = 256;
+
+pub fn encode_batch(
+    locations: &[Location],
+    precision: Precision,
+    out: &mut [u64],
+) -> Result<(), GeohashError> {
+    assert_eq!(locations.len(), out.len(), "slices differ in length");
+    validate(locations, precision)?;
+    encode_locations(locations, precision, out);
+    Ok(())
+}
+
+// structure of arrays variant of encode_batch
+pub fn encode_batch_soa(
+    longitudes: &[f64],
+    latitudes: &[f64],
+    precision: Precision,
+    out: &mut [u64],
+) -> Result<(), GeohashError> {
+    assert!(
+        longitudes.len() == out.len() && latitudes.len() == out.len(),
+        "slices differ in length"
+    );
+    precision.validate()?;
+    for (&longitude, &latitude) in longitudes.iter().zip(latitudes) {
+        Location {
+            longitude,
+            latitude,
+        }
+        .validate()?;
+    }
+    encode_with(|i| (longitudes[i], latitudes[i]), precision, out);
+    Ok(())
+}
+
+pub fn encode_batch_strings(
+    locations: &[Location],
+    precision: Precision,
+    out: &mut [GeohashStr],
+) -> Result<(), GeohashError> {
+    assert_eq!(locations.len(), out.len(), "slices differ in length");
+    validate(locations, precision)?;
+    let mut bits = [0u64; CHUNK];
+    for (locations, out) in locations.chunks(CHUNK).zip(out.chunks_mut(CHUNK)) {
+        let bits = &mut bits[..out.len()];
+        encode_locations(locations, precision, bits);
+        for (hash, &bits) in out.iter_mut().zip(bits.iter()) {
+            *hash = GeohashBits::from_bits(bits, precision).hash_inline();
+        }
+    }
+    Ok(())
+}
+
+// the center of each cell
+pub fn decode_batch(
+    bits: &[u64],
+    precision: Precision,
+    out: &mut [Location],
+) -> Result<(), GeohashError> {
+    assert_eq!(bits.len(), out.len(), "slices differ in length");
+    precision.validate()?;
+    for &bits in bits {
+        GeohashBits::try_from_bits(bits, precision)?;
+    }
+    let max_binary_value = precision.max_binary_value();
+    let (lon_degrees, lat_degrees) = precision.cell_degrees();
+    let (lat_shift, lat_max_binary_value) = if precision.is_odd_characters() {
+        (1, 0.5 * max_binary_value)
+    } else {
+        (0, max_binary_value)
+    };
+
+    let mut lat_bits = [0u32; CHUNK];
+    let mut lon_bits = [0u32; CHUNK];
+    for (bits, out) in bits.chunks(CHUNK).zip(out.chunks_mut(CHUNK)) {
+        let lat_bits = &mut lat_bits[..out.len()];
+        let lon_bits = &mut lon_bits[..out.len()];
+        ops::deinterleave_slice(bits, lat_bits, lon_bits);
+        for ((location, &lat), &lon) in out.iter_mut().zip(lat_bits.iter()).zip(lon_bits.iter()) {
+            *location = Location {
+                longitude: bits_to_float(lon, &LONGITUDE_RANGE, max_binary_value)
+                    + 0.5 * lon_degrees,
+                latitude: bits_to_float(lat >> lat_shift, &LATITUDE_RANGE, lat_max_binary_value)
+                    + 0.5 * lat_degrees,
+            };
+        }
+    }
+    Ok(())
+}
+
+fn validate(locations: &[Location], precision: Precision) -> Result<(), GeohashError> {
+    precision.validate()?;
+    for location in locations {
+        location.validate()?;
+    }
+    Ok(())
+}
+
+// encode_batch for inputs that are already validated
+pub(crate) fn encode_locations(locations: &[Location], precision: Precision, out: &mut [u64]) {
+    encode_with(
+        |i| (locations[i].longitude, locations[i].latitude),
+        precision,
+        out,
+    );
+}
+
+fn encode_with<F>(coordinates: F, precision: Precision, out: &mut [u64])
+where
+    F: Fn(usize) -> (f64, f64),
+{
+    let max_binary_value = precision.max_binary_value();
+    let padding_mask = precision.padding_mask();
+    let mut lat_bits = [0u32; CHUNK];
+    let mut lon_bits = [0u32; CHUNK];
+    for (chunk, out) in out.chunks_mut(CHUNK).enumerate() {
+        let lat_bits = &mut lat_bits[..out.len()];
+        let lon_bits = &mut lon_bits[..out.len()];
+        for (i, (lat, lon)) in lat_bits.iter_mut().zip(lon_bits.iter_mut()).enumerate() {
+            let (longitude, latitude) = coordinates(chunk * CHUNK + i);
+            *lon = float_to_bits(longitude, &LONGITUDE_RANGE, max_binary_value);
+            *lat = float_to_bits(latitude, &LATITUDE_RANGE, max_binary_value);
+        }
+        ops::interleave_slice(lat_bits, lon_bits, out);
+        for bits in out.iter_mut() {
+            *bits &= padding_mask;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{decode_batch, encode_batch, encode_batch_soa, encode_batch_strings};
+    use crate::{GeohashBits, GeohashError, GeohashStr, Location, Precision};
+    use assert_approx_eq::assert_approx_eq;
+
+    // spread over the whole world and longer than a chunk
+    fn locations() -> Vec<Location> {
+        (0..1000)
+            .map(|i| Location {
+                longitude: -180.0 + 0.36 * i as f64,
+                latitude: 90.0 - 0.18 * i as f64,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_batch() {
+        let locations = locations();
+        for &precision in &[
+            Precision::Bits(26),
+            Precision::Bits(32),
+            Precision::Characters(5),
+            Precision::Characters(12),
+        ] {
+            let mut bits = vec![0; locations.len()];
+            encode_batch(&locations, precision, &mut bits).unwrap();
+            for (location, &bits) in locations.iter().zip(&bits) {
+                assert_eq!(bits, GeohashBits::from_location(location, precision).bits());
+            }
+
+            let longitudes: Vec<f64> = locations.iter().map(|l| l.longitude).collect();
+            let latitudes: Vec<f64> = locations.iter().map(|l| l.latitude).collect();
+            let mut soa = vec![0; locations.len()];
+            encode_batch_soa(&longitudes, &latitudes, precision, &mut soa).unwrap();
+            assert_eq!(soa, bits);
+        }
+    }
+
+    #[test]
+    fn test_encode_batch_strings() {
+        let locations = locations();
+        let mut hashes = vec![GeohashStr::default(); locations.len()];
+        encode_batch_strings(&locations, Precision::Characters(7), &mut hashes).unwrap();
+        for (location, hash) in locations.iter().zip(&hashes) {
+            assert_eq!(
+                hash.as_str(),
+                GeohashBits::from_location(location, Precision::Characters(7)).hash()
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_batch() {
+        let locations = locations();
+        for &precision in &[Precision::Bits(20), Precision::Characters(9)] {
+            let mut bits = vec![0; locations.len()];
+            encode_batch(&locations, precision, &mut bits).unwrap();
+            let mut centers = vec![
+                Location {
+                    longitude: 0.0,
+                    latitude: 0.0
+                };
+                locations.len()
+            ];
+            decode_batch(&bits, precision, &mut centers).unwrap();
+            for (&bits, center) in bits.iter().zip(&centers) {
+                let expected = GeohashBits::from_bits(bits, precision)
+                    .bounding_box()
+                    .center();
+                assert_approx_eq!(center.longitude, expected.longitude, 1e-9);
+                assert_approx_eq!(center.latitude, expected.latitude, 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut locations = locations();
+        let mut bits = vec![0; locations.len()];
+        locations[300].latitude = 91.0;
+        assert_eq!(
+            encode_batch(&locations, Precision::Bits(16), &mut bits),
+            Err(GeohashError::LatitudeOutOfRange(91.0))
+        );
+        assert!(bits.iter().all(|&bits| bits == 0));
+        // the error is past the first chunk, which must not be written either
+        let mut hashes = vec![GeohashStr::default(); locations.len()];
+        assert_eq!(
+            encode_batch_strings(&locations, Precision::Characters(7), &mut hashes),
+            Err(GeohashError::LatitudeOutOfRange(91.0))
+        );
+        assert!(hashes.iter().all(|hash| hash.as_str().is_empty()));
+        assert_eq!(
+            encode_batch(&locations[..1], Precision::Bits(33), &mut bits[..1]),
+            Err(GeohashError::PrecisionOutOfRange(33))
+        );
+
+        let mut centers = vec![
+            Location {
+                longitude: 0.0,
+                latitude: 0.0
+            };
+            2
+        ];
+        assert_eq!(
+            decode_batch(&[0, 1 << 40], Precision::Bits(20), &mut centers),
+            Err(GeohashError::BitsOutOfRange(1 << 40))
+        );
+        // the precision is checked even when there is nothing to convert
+        assert_eq!(
+            decode_batch(&[], Precision::Bits(200), &mut []),
+            Err(GeohashError::PrecisionOutOfRange(200))
+        );
+        assert_eq!(
+            encode_batch(&[], Precision::Bits(200), &mut []),
+            Err(GeohashError::PrecisionOutOfRange(200))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_length_mismatch() {
+        let mut bits = vec![0; 2];
+        let _ = encode_batch(&locations()[..3], Precision::Bits(16), &mut bits);
+    }
+}
@@ -1,18 +1,45 @@
-use crate::Location;
+use crate::geohash_bits::MAX_BINARY_PRECISION;
+use crate::location::{EARTH_RADIUS_METERS, RADIANS_PER_DEGREE};
+use crate::{cover_region, Coverage, GeohashBits, GeohashError, Location, Precision, Region};
+use std::ops::Range;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "crate::serialization::RawBoundingBox")
+)]
 pub struct BoundingBox {
     pub(crate) min: Location,
     pub(crate) max: Location,
 }
 
 impl BoundingBox {
+    // min longitude greater than max longitude is a box crossing the 180th
+    // meridian, extending east from min through 180 to max
+    pub fn new(min: &Location, max: &Location) -> BoundingBox {
+        match BoundingBox::try_new(min, max) {
+            Ok(bbox) => bbox,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    // a min latitude above the max one is reported as out of range
+    pub fn try_new(min: &Location, max: &Location) -> Result<BoundingBox, GeohashError> {
+        min.validate()?;
+        max.validate()?;
+        if min.latitude > max.latitude {
+            return Err(GeohashError::LatitudeOutOfRange(min.latitude));
+        }
+        Ok(BoundingBox {
+            min: *min,
+            max: *max,
+        })
+    }
+
     pub fn at(a: &Location) -> BoundingBox {
         a.validate_range();
-        BoundingBox {
-            min: a.clone(),
-            max: a.clone(),
-        }
+        BoundingBox { min: *a, max: *a }
     }
 
     pub fn enclosing<I>(locations: I) -> Option<BoundingBox>
@@ -29,6 +56,33 @@ impl BoundingBox {
         }
     }
 
+    // Every location within radius_meters of center, crossing the 180th
+    // meridian or spanning all longitudes as needed.
+    pub fn from_center_radius(center: &Location, radius_meters: f64) -> BoundingBox {
+        BoundingBox::at(center).expand_by_meters(radius_meters)
+    }
+
+    // Like enclosing, but goes the shorter way round, crossing the 180th
+    // meridian when the widest gap between the longitudes does not.
+    pub fn smallest_enclosing<I>(locations: I) -> Option<BoundingBox>
+    where
+        I: IntoIterator<Item = Location>,
+    {
+        let locations: Vec<Location> = locations.into_iter().collect();
+        let mut bbox = BoundingBox::enclosing(locations.iter().cloned())?;
+        let mut longitudes: Vec<f64> = locations.iter().map(|l| l.longitude).collect();
+        longitudes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mut widest_gap = longitudes[0] + 360.0 - longitudes[longitudes.len() - 1];
+        for pair in longitudes.windows(2) {
+            if pair[1] - pair[0] > widest_gap {
+                widest_gap = pair[1] - pair[0];
+                bbox.min.longitude = pair[1];
+                bbox.max.longitude = pair[0];
+            }
+        }
+        Some(bbox)
+    }
+
     pub fn min(&self) -> &Location {
         &self.min
     }
@@ -37,46 +91,426 @@ impl BoundingBox {
         &self.max
     }
 
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.min.longitude > self.max.longitude
+    }
+
     pub fn center(&self) -> Location {
+        let mut longitude = 0.5 * (self.min.longitude + self.max.longitude);
+        if self.crosses_antimeridian() {
+            longitude += if longitude > 0.0 { -180.0 } else { 180.0 };
+        }
         Location {
-            longitude: 0.5 * (self.min.longitude + self.max.longitude),
+            longitude,
             latitude: 0.5 * (self.min.latitude + self.max.latitude),
         }
     }
 
+    pub fn contains(&self, location: &Location) -> bool {
+        self.unwrapped().any(|bbox| {
+            bbox.min.longitude <= location.longitude
+                && location.longitude <= bbox.max.longitude
+                && bbox.min.latitude <= location.latitude
+                && location.latitude <= bbox.max.latitude
+        })
+    }
+
     pub fn intersects(&self, other: &BoundingBox) -> bool {
-        // TODO account for wraparound at 180th meridian
-        if self.max.longitude < other.min.longitude
+        self.unwrapped().any(|bbox| {
+            other
+                .unwrapped()
+                .any(|other| bbox.intersects_unwrapped(&other))
+        })
+    }
+
+    fn intersects_unwrapped(&self, other: &BoundingBox) -> bool {
+        !(self.max.longitude < other.min.longitude
             || self.max.latitude < other.min.latitude
             || self.min.longitude > other.max.longitude
-            || self.min.latitude > other.max.latitude
-        {
-            return false;
+            || self.min.latitude > other.max.latitude)
+    }
+
+    pub fn contains_box(&self, other: &BoundingBox) -> bool {
+        other.unwrapped().all(|other| {
+            self.unwrapped().any(|bbox| {
+                bbox.min.longitude <= other.min.longitude
+                    && bbox.min.latitude <= other.min.latitude
+                    && other.max.longitude <= bbox.max.longitude
+                    && other.max.latitude <= bbox.max.latitude
+            })
+        })
+    }
+
+    // The overlap of the two boxes, empty when they are disjoint. Two boxes
+    // that together wrap all the way round can overlap in two separate boxes.
+    pub fn intersection(&self, other: &BoundingBox) -> Vec<BoundingBox> {
+        let mut pieces: Vec<BoundingBox> = Vec::with_capacity(2);
+        for bbox in self.unwrapped() {
+            for other in other.unwrapped() {
+                let piece = BoundingBox {
+                    min: Location {
+                        longitude: bbox.min.longitude.max(other.min.longitude),
+                        latitude: bbox.min.latitude.max(other.min.latitude),
+                    },
+                    max: Location {
+                        longitude: bbox.max.longitude.min(other.max.longitude),
+                        latitude: bbox.max.latitude.min(other.max.latitude),
+                    },
+                };
+                if piece.min.longitude <= piece.max.longitude
+                    && piece.min.latitude <= piece.max.latitude
+                {
+                    pieces.push(piece);
+                }
+            }
+        }
+        // rejoin pieces split at the 180th meridian
+        let west = pieces.iter().position(|piece| piece.max.longitude == 180.0);
+        let east = pieces
+            .iter()
+            .position(|piece| piece.min.longitude == -180.0);
+        if let (Some(west), Some(east)) = (west, east) {
+            if west != east {
+                let mut joined = pieces[west];
+                joined.max.longitude = pieces[east].max.longitude;
+                if joined.min.longitude <= joined.max.longitude {
+                    joined.min.longitude = -180.0;
+                    joined.max.longitude = 180.0;
+                }
+                pieces
+                    .retain(|piece| piece.max.longitude != 180.0 && piece.min.longitude != -180.0);
+                pieces.push(joined);
+            }
+        }
+        pieces.sort_by(|a, b| a.min.longitude.partial_cmp(&b.min.longitude).unwrap());
+        pieces
+    }
+
+    // The smallest box containing both, going the shorter way round.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        // the span needed starting at a's west edge to reach the east edges of both
+        let span_from = |a: &BoundingBox, b: &BoundingBox| {
+            let offset = (b.min.longitude - a.min.longitude).rem_euclid(360.0);
+            a.width_degrees().max(offset + b.width_degrees())
+        };
+        let (start, span) = {
+            let from_self = span_from(self, other);
+            let from_other = span_from(other, self);
+            if from_self <= from_other {
+                (self.min.longitude, from_self)
+            } else {
+                (other.min.longitude, from_other)
+            }
+        };
+        let (min_longitude, max_longitude) = if span >= 360.0 {
+            (-180.0, 180.0)
+        } else if start + span > 180.0 {
+            (start, start + span - 360.0)
+        } else {
+            (start, start + span)
+        };
+        BoundingBox {
+            min: Location {
+                longitude: min_longitude,
+                latitude: self.min.latitude.min(other.min.latitude),
+            },
+            max: Location {
+                longitude: max_longitude,
+                latitude: self.max.latitude.max(other.max.latitude),
+            },
+        }
+    }
+
+    pub fn width_degrees(&self) -> f64 {
+        if self.crosses_antimeridian() {
+            self.max.longitude + 360.0 - self.min.longitude
+        } else {
+            self.max.longitude - self.min.longitude
+        }
+    }
+
+    pub fn height_degrees(&self) -> f64 {
+        self.max.latitude - self.min.latitude
+    }
+
+    // along the parallel through the center
+    pub fn width_meters(&self) -> f64 {
+        EARTH_RADIUS_METERS
+            * (RADIANS_PER_DEGREE * self.center().latitude).cos()
+            * RADIANS_PER_DEGREE
+            * self.width_degrees()
+    }
+
+    pub fn height_meters(&self) -> f64 {
+        EARTH_RADIUS_METERS * RADIANS_PER_DEGREE * self.height_degrees()
+    }
+
+    pub fn area_m2(&self) -> f64 {
+        let sin_max = (RADIANS_PER_DEGREE * self.max.latitude).sin();
+        let sin_min = (RADIANS_PER_DEGREE * self.min.latitude).sin();
+        EARTH_RADIUS_METERS
+            * EARTH_RADIUS_METERS
+            * RADIANS_PER_DEGREE
+            * self.width_degrees()
+            * (sin_max - sin_min)
+    }
+
+    // Grows the box by at least the given distance on every side. Longitude
+    // growth uses the parallel furthest from the equator so the result
+    // covers everything within that distance, and spans all longitudes once
+    // it reaches a pole.
+    pub fn expand_by_meters(&self, meters: f64) -> BoundingBox {
+        assert!(meters >= 0.0, "distance out of range");
+        let delta_latitude = meters / (EARTH_RADIUS_METERS * RADIANS_PER_DEGREE);
+        let min_latitude = (self.min.latitude - delta_latitude).max(-90.0);
+        let max_latitude = (self.max.latitude + delta_latitude).min(90.0);
+        let widest = min_latitude.abs().max(max_latitude.abs());
+
+        let mut min_longitude = -180.0;
+        let mut max_longitude = 180.0;
+        if widest < 90.0 {
+            let delta_longitude = delta_latitude / (RADIANS_PER_DEGREE * widest).cos();
+            if self.width_degrees() + 2.0 * delta_longitude < 360.0 {
+                min_longitude = self.min.longitude - delta_longitude;
+                max_longitude = self.max.longitude + delta_longitude;
+                if min_longitude < -180.0 {
+                    min_longitude += 360.0;
+                }
+                if max_longitude > 180.0 {
+                    max_longitude -= 360.0;
+                }
+            }
+        }
+        BoundingBox {
+            min: Location {
+                longitude: min_longitude,
+                latitude: min_latitude,
+            },
+            max: Location {
+                longitude: max_longitude,
+                latitude: max_latitude,
+            },
+        }
+    }
+
+    // counterclockwise from the south west
+    pub fn corners(&self) -> [Location; 4] {
+        [
+            self.min,
+            Location {
+                longitude: self.max.longitude,
+                latitude: self.min.latitude,
+            },
+            self.max,
+            Location {
+                longitude: self.min.longitude,
+                latitude: self.max.latitude,
+            },
+        ]
+    }
+
+    // south west, south east, north east and north west quarters, split at
+    // the center
+    pub fn split_quadrants(&self) -> [BoundingBox; 4] {
+        let center = self.center();
+        let mut east_longitude = center.longitude;
+        if self.crosses_antimeridian() && east_longitude == 180.0 {
+            east_longitude = -180.0;
+        }
+        let quadrant = |min_longitude, min_latitude, max_longitude, max_latitude| BoundingBox {
+            min: Location {
+                longitude: min_longitude,
+                latitude: min_latitude,
+            },
+            max: Location {
+                longitude: max_longitude,
+                latitude: max_latitude,
+            },
+        };
+        [
+            quadrant(
+                self.min.longitude,
+                self.min.latitude,
+                center.longitude,
+                center.latitude,
+            ),
+            quadrant(
+                east_longitude,
+                self.min.latitude,
+                self.max.longitude,
+                center.latitude,
+            ),
+            quadrant(
+                east_longitude,
+                center.latitude,
+                self.max.longitude,
+                self.max.latitude,
+            ),
+            quadrant(
+                self.min.longitude,
+                center.latitude,
+                center.longitude,
+                self.max.latitude,
+            ),
+        ]
+    }
+
+    // the box split at the 180th meridian into boxes that do not cross it
+    fn unwrapped(&self) -> impl Iterator<Item = BoundingBox> {
+        let (west, east) = if self.crosses_antimeridian() {
+            let mut west = *self;
+            let mut east = *self;
+            west.max.longitude = 180.0;
+            east.min.longitude = -180.0;
+            (west, Some(east))
+        } else {
+            (*self, None)
+        };
+        std::iter::once(west).chain(east)
+    }
+
+    // Sorted, disjoint ranges of bits() values at Precision::Bits(bit_precision)
+    // covering every cell that intersects the box. Boundary cells are only
+    // subdivided while the ranges fit in max_ranges, then the closest ranges
+    // are merged, so the result may also include cells outside the box. A
+    // max_ranges of 0 is treated as 1. Precisions go up to 31, the end of a
+    // Bits(32) range would not fit a u64.
+    pub fn to_ranges(
+        &self,
+        bit_precision: u8,
+        max_ranges: usize,
+    ) -> Result<Vec<Range<u64>>, GeohashError> {
+        if !(1..MAX_BINARY_PRECISION).contains(&bit_precision) {
+            return Err(GeohashError::PrecisionOutOfRange(bit_precision));
+        }
+        let max_ranges = max_ranges.max(1);
+
+        let mut cells = cover_region(self, Precision::Bits(1));
+        loop {
+            let ranges = cell_ranges(&cells, bit_precision);
+            let refined = cells.iter().any(|(bits, coverage)| {
+                *coverage == Coverage::Partial
+                    && bits.precision().binary_precision() < bit_precision
+            });
+            // one level past the budget, so merging can pick the closest ranges
+            if ranges.len() > max_ranges || !refined {
+                return Ok(merge_closest(ranges, max_ranges));
+            }
+            cells = cells
+                .into_iter()
+                .flat_map(|(bits, coverage)| {
+                    if coverage == Coverage::Partial
+                        && bits.precision().binary_precision() < bit_precision
+                    {
+                        bits.children()
+                            .filter_map(|child| {
+                                self.coverage(&child.bounding_box())
+                                    .map(|coverage| (child, coverage))
+                            })
+                            .collect()
+                    } else {
+                        vec![(bits, coverage)]
+                    }
+                })
+                .collect();
         }
-        true
     }
 
     pub fn encompass(&mut self, location: &Location) {
         location.validate_range();
-        if location.longitude < self.min.longitude {
+        if self.crosses_antimeridian() {
+            // a longitude outside the box is in the gap between max and min,
+            // grow whichever side is closer
+            if self.max.longitude < location.longitude && location.longitude < self.min.longitude {
+                if location.longitude - self.max.longitude < self.min.longitude - location.longitude
+                {
+                    self.max.longitude = location.longitude;
+                } else {
+                    self.min.longitude = location.longitude;
+                }
+            }
+        } else if location.longitude < self.min.longitude {
             self.min.longitude = location.longitude;
+        } else if location.longitude > self.max.longitude {
+            self.max.longitude = location.longitude;
         }
         if location.latitude < self.min.latitude {
             self.min.latitude = location.latitude;
         }
-        if location.longitude > self.max.longitude {
-            self.max.longitude = location.longitude;
-        }
         if location.latitude > self.max.latitude {
             self.max.latitude = location.latitude;
         }
     }
 }
 
+// the z-ordered cells as ranges at bit_precision, adjacent ones joined
+fn cell_ranges(cells: &[(GeohashBits, Coverage)], bit_precision: u8) -> Vec<Range<u64>> {
+    let mut ranges: Vec<Range<u64>> = Vec::new();
+    for (bits, _) in cells {
+        let shift = 2 * (bit_precision - bits.precision().binary_precision());
+        let range = (bits.bits() << shift)..((bits.bits() + 1) << shift);
+        match ranges.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => ranges.push(range),
+        }
+    }
+    ranges
+}
+
+// keeps the widest gaps between the ranges, closes all the others
+fn merge_closest(ranges: Vec<Range<u64>>, max_ranges: usize) -> Vec<Range<u64>> {
+    if ranges.len() <= max_ranges {
+        return ranges;
+    }
+    let mut gaps: Vec<usize> = (1..ranges.len()).collect();
+    gaps.sort_by_key(|&i| std::cmp::Reverse(ranges[i].start - ranges[i - 1].end));
+    let mut kept = gaps[..max_ranges - 1].to_vec();
+    kept.sort_unstable();
+    let mut merged = Vec::with_capacity(max_ranges);
+    let mut start = ranges[0].start;
+    for i in kept {
+        merged.push(start..ranges[i - 1].end);
+        start = ranges[i].start;
+    }
+    merged.push(start..ranges[ranges.len() - 1].end);
+    merged
+}
+
+impl Region for BoundingBox {
+    fn coverage(&self, cell: &BoundingBox) -> Option<Coverage> {
+        if self.crosses_antimeridian() {
+            // cells never cross the 180th meridian so each lies in one half
+            return self
+                .unwrapped()
+                .filter_map(|bbox| bbox.coverage(cell))
+                .max_by_key(|coverage| *coverage == Coverage::Full);
+        }
+        // cells are half open, so a cell only touching the box with its east or
+        // north edge holds none of it, unless that edge is the end of the range
+        if !self.intersects(cell)
+            || (cell.max.longitude <= self.min.longitude && cell.max.longitude < 180.0)
+            || (cell.max.latitude <= self.min.latitude && cell.max.latitude < 90.0)
+        {
+            None
+        } else if self.min.longitude <= cell.min.longitude
+            && self.min.latitude <= cell.min.latitude
+            && cell.max.longitude <= self.max.longitude
+            && cell.max.latitude <= self.max.latitude
+        {
+            Some(Coverage::Full)
+        } else {
+            Some(Coverage::Partial)
+        }
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::Location;
-    use crate::BoundingBox;
+    use crate::{
+        BoundingBox, Coverage, GeohashBits, GeohashError, GeohashIterator, Precision, Region,
+    };
     use assert_approx_eq::assert_approx_eq;
 
     fn bbox() -> BoundingBox {
@@ -102,6 +536,52 @@ mod tests {
         assert_approx_eq!(bbox.max().longitude, 3.0, 1e-5);
     }
 
+    #[test]
+    fn test_try_new() {
+        let min = Location {
+            longitude: 2.0,
+            latitude: 1.0,
+        };
+        let max = Location {
+            longitude: 3.0,
+            latitude: 2.0,
+        };
+        assert_eq!(BoundingBox::try_new(&min, &max), Ok(bbox()));
+        // min longitude east of max crosses the 180th meridian
+        let east = Location {
+            longitude: -170.0,
+            latitude: 2.0,
+        };
+        assert!(BoundingBox::try_new(&min, &east).is_ok());
+        assert_eq!(
+            BoundingBox::try_new(&max, &min),
+            Err(GeohashError::LatitudeOutOfRange(2.0))
+        );
+        let invalid = Location {
+            longitude: 181.0,
+            latitude: 2.0,
+        };
+        assert_eq!(
+            BoundingBox::try_new(&min, &invalid),
+            Err(GeohashError::LongitudeOutOfRange(181.0))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "latitude out of range")]
+    fn test_new_inverted() {
+        BoundingBox::new(
+            &Location {
+                longitude: 0.0,
+                latitude: 1.0,
+            },
+            &Location {
+                longitude: 1.0,
+                latitude: 0.0,
+            },
+        );
+    }
+
     #[test]
     fn test_center() {
         let center = bbox().center();
@@ -140,4 +620,370 @@ mod tests {
         .unwrap();
         assert_eq!(other.intersects(&bbox()), false);
     }
+
+    #[test]
+    fn test_to_ranges() {
+        let bounds = GeohashBits::from_hash("dp3").bounding_box();
+        let ranges = bounds.to_ranges(16, usize::MAX).unwrap();
+        assert!(ranges.windows(2).all(|pair| pair[0].end < pair[1].start));
+
+        let cells: Vec<u64> = GeohashIterator::new(bounds, 16)
+            .map(|bits| bits.bits())
+            .collect();
+        assert!(cells
+            .iter()
+            .all(|bits| ranges.iter().any(|range| range.contains(bits))));
+        let covered: u64 = ranges.iter().map(|range| range.end - range.start).sum();
+        assert_eq!(covered, cells.len() as u64);
+        assert!(ranges.len() < 2000);
+
+        let limited = bounds.to_ranges(16, 4).unwrap();
+        assert!(limited.len() <= 4);
+        assert!(cells
+            .iter()
+            .all(|bits| limited.iter().any(|range| range.contains(bits))));
+    }
+
+    #[test]
+    fn test_to_ranges_single_cell() {
+        let cell = GeohashBits::from_location(
+            &Location {
+                longitude: -0.1,
+                latitude: 51.5,
+            },
+            Precision::Bits(8),
+        );
+        let center = cell.bounding_box().center();
+        let bounds = BoundingBox::at(&center);
+        let range = cell.bits()..cell.bits() + 1;
+        assert_eq!(bounds.to_ranges(8, 10), Ok(vec![range]));
+    }
+
+    #[test]
+    fn test_to_ranges_precision() {
+        assert_eq!(
+            bbox().to_ranges(32, 10),
+            Err(GeohashError::PrecisionOutOfRange(32))
+        );
+        assert_eq!(
+            bbox().to_ranges(0, 10),
+            Err(GeohashError::PrecisionOutOfRange(0))
+        );
+    }
+
+    #[test]
+    fn test_to_ranges_budget() {
+        // the complete cover at 31 bits would take millions of cells
+        let bounds = GeohashBits::from_hash("gcpuv").bounding_box();
+        let ranges = bounds.to_ranges(31, 4).unwrap();
+        assert!(!ranges.is_empty() && ranges.len() <= 4);
+        assert_eq!(bounds.to_ranges(31, 0), bounds.to_ranges(31, 1));
+        assert_eq!(bounds.to_ranges(31, 0).unwrap().len(), 1);
+        let cells: Vec<u64> = GeohashIterator::new(bounds, 31)
+            .take(1000)
+            .map(|bits| bits.bits())
+            .collect();
+        assert!(cells
+            .iter()
+            .all(|bits| ranges.iter().any(|range| range.contains(bits))));
+    }
+
+    fn fiji() -> Vec<Location> {
+        vec![
+            Location {
+                longitude: 177.4,
+                latitude: -17.7,
+            },
+            Location {
+                longitude: -179.9,
+                latitude: -16.2,
+            },
+            Location {
+                longitude: 178.4,
+                latitude: -18.1,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_smallest_enclosing() {
+        let wide = BoundingBox::enclosing(fiji()).unwrap();
+        assert!(!wide.crosses_antimeridian());
+        assert_approx_eq!(wide.min().longitude, -179.9, 1e-5);
+        assert_approx_eq!(wide.max().longitude, 178.4, 1e-5);
+
+        let bbox = BoundingBox::smallest_enclosing(fiji()).unwrap();
+        assert!(bbox.crosses_antimeridian());
+        assert_approx_eq!(bbox.min().longitude, 177.4, 1e-5);
+        assert_approx_eq!(bbox.max().longitude, -179.9, 1e-5);
+        assert_approx_eq!(bbox.min().latitude, -18.1, 1e-5);
+        assert_approx_eq!(bbox.max().latitude, -16.2, 1e-5);
+        assert_approx_eq!(bbox.center().longitude, 178.75, 1e-5);
+
+        assert_eq!(BoundingBox::smallest_enclosing(vec![]), None);
+        let bbox = BoundingBox::smallest_enclosing(vec![
+            Location {
+                latitude: 1.0,
+                longitude: 3.0,
+            },
+            Location {
+                latitude: 2.0,
+                longitude: 2.0,
+            },
+        ]);
+        assert_eq!(bbox, Some(self::bbox()));
+    }
+
+    #[test]
+    fn test_antimeridian() {
+        let bbox = BoundingBox::smallest_enclosing(fiji()).unwrap();
+        assert!(bbox.contains(&Location {
+            longitude: 180.0,
+            latitude: -17.0
+        }));
+        assert!(bbox.contains(&Location {
+            longitude: -180.0,
+            latitude: -17.0
+        }));
+        assert!(!bbox.contains(&Location {
+            longitude: 0.0,
+            latitude: -17.0
+        }));
+
+        let east = GeohashBits::from_location(
+            &Location {
+                longitude: -179.95,
+                latitude: -17.0,
+            },
+            Precision::Characters(4),
+        );
+        let west = GeohashBits::from_location(
+            &Location {
+                longitude: 178.0,
+                latitude: -17.0,
+            },
+            Precision::Characters(4),
+        );
+        let outside = GeohashBits::from_hash("rg");
+        assert!(bbox.intersects(&east.bounding_box()));
+        assert!(east.bounding_box().intersects(&bbox));
+        assert!(bbox.intersects(&west.bounding_box()));
+        assert!(!bbox.intersects(&outside.bounding_box()));
+        assert!(!bbox.intersects(&self::bbox()));
+        assert!(bbox.intersects(&BoundingBox::new(
+            &Location {
+                longitude: 179.0,
+                latitude: -20.0,
+            },
+            &Location {
+                longitude: -179.0,
+                latitude: 0.0,
+            },
+        )));
+
+        assert_eq!(bbox.coverage(&west.bounding_box()), Some(Coverage::Full));
+        assert_eq!(bbox.coverage(&outside.bounding_box()), None);
+    }
+
+    #[test]
+    fn test_encompass_antimeridian() {
+        let mut bbox = BoundingBox::smallest_enclosing(fiji()).unwrap();
+        bbox.encompass(&Location {
+            longitude: -175.0,
+            latitude: -17.0,
+        });
+        assert_approx_eq!(bbox.max().longitude, -175.0, 1e-5);
+        bbox.encompass(&Location {
+            longitude: 170.0,
+            latitude: -17.0,
+        });
+        assert_approx_eq!(bbox.min().longitude, 170.0, 1e-5);
+        bbox.encompass(&Location {
+            longitude: 179.0,
+            latitude: -10.0,
+        });
+        assert_approx_eq!(bbox.min().longitude, 170.0, 1e-5);
+        assert_approx_eq!(bbox.max().longitude, -175.0, 1e-5);
+        assert_approx_eq!(bbox.max().latitude, -10.0, 1e-5);
+    }
+
+    #[test]
+    fn test_to_ranges_antimeridian() {
+        let bbox = BoundingBox::smallest_enclosing(fiji()).unwrap();
+        let ranges = bbox.to_ranges(10, usize::MAX).unwrap();
+        let cells: Vec<u64> = GeohashIterator::new(bbox, 10)
+            .map(|bits| bits.bits())
+            .collect();
+        assert!(cells
+            .iter()
+            .all(|bits| ranges.iter().any(|range| range.contains(bits))));
+    }
+
+    fn bounds(
+        min_longitude: f64,
+        min_latitude: f64,
+        max_longitude: f64,
+        max_latitude: f64,
+    ) -> BoundingBox {
+        BoundingBox::new(
+            &Location {
+                longitude: min_longitude,
+                latitude: min_latitude,
+            },
+            &Location {
+                longitude: max_longitude,
+                latitude: max_latitude,
+            },
+        )
+    }
+
+    #[test]
+    fn test_contains_box() {
+        let outer = bounds(0.0, 0.0, 10.0, 10.0);
+        assert!(outer.contains_box(&bounds(1.0, 1.0, 2.0, 2.0)));
+        assert!(outer.contains_box(&outer));
+        assert!(!outer.contains_box(&bounds(5.0, 5.0, 11.0, 6.0)));
+
+        let crossing = bounds(170.0, -10.0, -170.0, 10.0);
+        assert!(crossing.contains_box(&bounds(175.0, 0.0, 180.0, 1.0)));
+        assert!(crossing.contains_box(&bounds(-180.0, 0.0, -175.0, 1.0)));
+        assert!(crossing.contains_box(&bounds(175.0, 0.0, -175.0, 1.0)));
+        assert!(!crossing.contains_box(&bounds(160.0, 0.0, -175.0, 1.0)));
+        assert!(!crossing.contains_box(&bounds(-175.0, 0.0, 175.0, 1.0)));
+        assert!(bounds(-180.0, -90.0, 180.0, 90.0).contains_box(&crossing));
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = bounds(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(
+            a.intersection(&bounds(5.0, 5.0, 15.0, 15.0)),
+            vec![bounds(5.0, 5.0, 10.0, 10.0)]
+        );
+        assert!(a.intersection(&bounds(20.0, 0.0, 30.0, 10.0)).is_empty());
+
+        let crossing = bounds(170.0, -10.0, -170.0, 10.0);
+        assert_eq!(
+            crossing.intersection(&bounds(175.0, -20.0, -175.0, 0.0)),
+            vec![bounds(175.0, -10.0, -175.0, 0.0)]
+        );
+        assert_eq!(
+            crossing.intersection(&bounds(0.0, -20.0, 180.0, 0.0)),
+            vec![bounds(170.0, -10.0, 180.0, 0.0)]
+        );
+        // together they wrap all the way round, overlapping at both ends
+        assert_eq!(
+            crossing.intersection(&bounds(-175.0, -10.0, 175.0, 10.0)),
+            vec![
+                bounds(-175.0, -10.0, -170.0, 10.0),
+                bounds(170.0, -10.0, 175.0, 10.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_union() {
+        assert_eq!(
+            bounds(0.0, 0.0, 10.0, 10.0).union(&bounds(20.0, -5.0, 30.0, 5.0)),
+            bounds(0.0, -5.0, 30.0, 10.0)
+        );
+        assert_eq!(
+            bounds(0.0, 0.0, 10.0, 10.0).union(&bounds(2.0, 2.0, 3.0, 3.0)),
+            bounds(0.0, 0.0, 10.0, 10.0)
+        );
+        // the shorter way round crosses the 180th meridian
+        assert_eq!(
+            bounds(170.0, 0.0, 175.0, 1.0).union(&bounds(-175.0, 0.0, -170.0, 1.0)),
+            bounds(170.0, 0.0, -170.0, 1.0)
+        );
+        assert_eq!(
+            bounds(170.0, 0.0, -170.0, 1.0).union(&bounds(-100.0, 0.0, 100.0, 1.0)),
+            bounds(170.0, 0.0, 100.0, 1.0)
+        );
+        assert_eq!(
+            bounds(170.0, 0.0, -170.0, 1.0).union(&bounds(-175.0, 0.0, 175.0, 1.0)),
+            bounds(-180.0, 0.0, 180.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let crossing = bounds(170.0, -10.0, -170.0, 10.0);
+        assert_approx_eq!(crossing.width_degrees(), 20.0, 1e-9);
+        assert_approx_eq!(crossing.height_degrees(), 20.0, 1e-9);
+
+        let equator = bounds(0.0, -0.5, 1.0, 0.5);
+        assert_approx_eq!(equator.height_meters(), 111_194.9, 0.1);
+        assert_approx_eq!(equator.width_meters(), 111_194.9, 0.1);
+        assert_approx_eq!(bounds(0.0, 59.5, 1.0, 60.5).width_meters(), 55_597.5, 0.1);
+
+        let world = bounds(-180.0, -90.0, 180.0, 90.0);
+        let sphere = 4.0 * std::f64::consts::PI * 6_371_000.0 * 6_371_000.0;
+        assert_approx_eq!(world.area_m2() / sphere, 1.0, 1e-12);
+        assert_approx_eq!(
+            bounds(0.0, 0.0, 180.0, 90.0).area_m2() / sphere,
+            0.25,
+            1e-12
+        );
+        assert_approx_eq!(
+            crossing.area_m2(),
+            bounds(0.0, -10.0, 20.0, 10.0).area_m2(),
+            1e-3
+        );
+    }
+
+    #[test]
+    fn test_expand_by_meters() {
+        let center = Location {
+            longitude: 179.9,
+            latitude: 0.0,
+        };
+        let bbox = BoundingBox::from_center_radius(&center, 111_194.9);
+        assert!(bbox.crosses_antimeridian());
+        assert_approx_eq!(bbox.min().longitude, 178.9, 1e-3);
+        assert_approx_eq!(bbox.max().longitude, -179.1, 1e-3);
+        assert_approx_eq!(bbox.min().latitude, -1.0, 1e-5);
+        assert_approx_eq!(bbox.max().latitude, 1.0, 1e-5);
+
+        let pole = BoundingBox::from_center_radius(
+            &Location {
+                longitude: 10.0,
+                latitude: 89.5,
+            },
+            111_194.9,
+        );
+        assert_eq!(pole.width_degrees(), 360.0);
+        assert_eq!(pole.max().latitude, 90.0);
+        assert_approx_eq!(pole.min().latitude, 88.5, 1e-5);
+
+        let north = bounds(0.0, 59.0, 1.0, 60.0).expand_by_meters(111_194.9);
+        assert_approx_eq!(north.max().latitude, 61.0, 1e-5);
+        assert!(north.min().longitude < -1.9);
+    }
+
+    #[test]
+    fn test_corners_and_quadrants() {
+        let bbox = bbox();
+        let corners = bbox.corners();
+        assert_eq!(corners[0], *bbox.min());
+        assert_eq!(corners[2], *bbox.max());
+        assert_eq!(
+            corners[1],
+            Location {
+                longitude: 3.0,
+                latitude: 1.0
+            }
+        );
+        let quadrants = bbox.split_quadrants();
+        assert_eq!(quadrants[0], bounds(2.0, 1.0, 2.5, 1.5));
+        assert_eq!(quadrants[2], bounds(2.5, 1.5, 3.0, 2.0));
+
+        let crossing = bounds(170.0, -10.0, -170.0, 10.0);
+        let quadrants = crossing.split_quadrants();
+        assert_eq!(quadrants[0], bounds(170.0, -10.0, 180.0, 0.0));
+        assert_eq!(quadrants[1], bounds(-180.0, -10.0, -170.0, 0.0));
+        assert!(quadrants
+            .iter()
+            .all(|quadrant| crossing.contains_box(quadrant)));
+    }
 }
@@ -0,0 +1,785 @@
+use crate::geohash_bits::MAX_BINARY_PRECISION;
+use crate::location::{EARTH_RADIUS_METERS, RADIANS_PER_DEGREE};
+use crate::*;
+
+const MERCATOR_MAX: f64 = 20_037_726.37;
+// a row has 2^bits cells, keep tiny polar circles to 4096 cells per row
+const MAX_POLAR_BIT_PRECISION: u8 = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Coverage {
+    Full,
+    Partial,
+}
+
+pub trait Region {
+    // None when the cell is disjoint from the region
+    fn coverage(&self, cell: &BoundingBox) -> Option<Coverage>;
+}
+
+// Cells guaranteed to contain every point within radius_meters of center,
+// chosen like redis geohashGetAreasByRadius: the center cell at a precision
+// estimated from the radius plus those of its eight neighbors that can
+// intersect the circle. Circles containing a pole span every longitude, they
+// get the complete rows of cells around the pole instead.
+pub fn cover_circle(center: &Location, radius_meters: f64) -> Vec<GeohashBits> {
+    center.validate_range();
+    assert!(radius_meters >= 0.0, "radius out of range");
+
+    let extent = CircleExtent::new(center, radius_meters);
+    let mut bit_precision = estimate_bit_precision(radius_meters, center.latitude);
+    if extent.contains_pole() {
+        let bounds = BoundingBox::new(
+            &Location {
+                longitude: -180.0,
+                latitude: extent.min_latitude,
+            },
+            &Location {
+                longitude: 180.0,
+                latitude: extent.max_latitude,
+            },
+        );
+        return GeohashIterator::new(bounds, bit_precision.min(MAX_POLAR_BIT_PRECISION)).collect();
+    }
+    let mut cell = GeohashBits::from_location(center, Precision::Bits(bit_precision));
+    while bit_precision > 1 && !extent.within_neighbors_of(&cell) {
+        bit_precision -= 1;
+        cell = GeohashBits::from_location(center, Precision::Bits(bit_precision));
+    }
+
+    let mut cells: Vec<GeohashBits> = Vec::with_capacity(9);
+    for bits in cell.neighbors().to_array().iter().flatten() {
+        // at the coarsest precisions east and west can wrap to the same cell
+        if cells.iter().any(|other| other.bits() == bits.bits()) {
+            continue;
+        }
+        if extent.intersects(&bits.bounding_box()) {
+            cells.push(*bits);
+        }
+    }
+    cells
+}
+
+// Builds compact covers mixing precisions: cells entirely inside the region
+// stay coarse while boundary cells are subdivided, level by level, until they
+// reach max_precision or splitting another cell would exceed max_cells.
+// Covers never go coarser than min_precision, so max_cells can be exceeded
+// when the region needs more than that many cells at min_precision.
+#[derive(Clone, Copy, Debug)]
+pub struct CoverBuilder {
+    min_precision: Precision,
+    max_precision: Precision,
+    max_cells: usize,
+}
+
+impl Default for CoverBuilder {
+    fn default() -> CoverBuilder {
+        CoverBuilder {
+            min_precision: Precision::Bits(1),
+            max_precision: Precision::Bits(16),
+            max_cells: 8,
+        }
+    }
+}
+
+impl CoverBuilder {
+    pub fn new() -> CoverBuilder {
+        CoverBuilder::default()
+    }
+
+    pub fn min_precision(mut self, precision: Precision) -> CoverBuilder {
+        self.min_precision = precision;
+        self
+    }
+
+    pub fn max_precision(mut self, precision: Precision) -> CoverBuilder {
+        self.max_precision = precision;
+        self
+    }
+
+    pub fn max_cells(mut self, max_cells: usize) -> CoverBuilder {
+        self.max_cells = max_cells;
+        self
+    }
+
+    pub fn cover<R: Region>(&self, region: &R) -> Vec<GeohashBits> {
+        self.validate();
+        self.refine(
+            |precision| cover_region(region, precision),
+            |level, i, limit| children_within(region, &level[i], limit),
+        )
+    }
+
+    pub(crate) fn validate(&self) {
+        let min_bits = self.min_precision.significant_bits();
+        let max_bits = self.max_precision.significant_bits();
+        assert!(
+            matches!(
+                (self.min_precision, self.max_precision),
+                (Precision::Bits(_), Precision::Bits(_))
+                    | (Precision::Characters(_), Precision::Characters(_))
+            ),
+            "min and max precision must both be bits or both be characters"
+        );
+        assert!(
+            self.max_precision.validate().is_ok() && (1..=max_bits).contains(&min_bits),
+            "precision out of range"
+        );
+    }
+
+    // Subdivides the partial cells of the min_precision cover within the
+    // budget. children_of(level, i, limit) gives the children_within limit
+    // of level[i], it is only called for candidates that can still be split
+    // and is given the whole level so the level can be computed in parallel.
+    pub(crate) fn refine<C, F>(&self, cover: C, mut children_of: F) -> Vec<GeohashBits>
+    where
+        C: FnOnce(Precision) -> Vec<(GeohashBits, Coverage)>,
+        F: FnMut(&[GeohashBits], usize, usize) -> Option<Vec<(GeohashBits, Coverage)>>,
+    {
+        let max_bits = self.max_precision.significant_bits();
+        let mut cells = Vec::new();
+        let mut candidates = Vec::new();
+        for (bits, coverage) in cover(self.min_precision) {
+            match coverage {
+                Coverage::Full => cells.push(bits),
+                Coverage::Partial => candidates.push(bits),
+            }
+        }
+
+        // breadth first so the cell budget is spent on the coarsest cells,
+        // candidates only ever holds the next level
+        while !candidates.is_empty() {
+            let level = std::mem::take(&mut candidates);
+            for (i, bits) in level.iter().enumerate() {
+                // the cells left for the children once every other cell and
+                // candidate is counted
+                let limit = self
+                    .max_cells
+                    .checked_sub(cells.len() + candidates.len() + level.len() - i - 1);
+                let children = match limit {
+                    Some(limit) if bits.precision().significant_bits() < max_bits => {
+                        children_of(&level, i, limit)
+                    }
+                    _ => None,
+                };
+                match children {
+                    Some(children) => {
+                        for (child, coverage) in children {
+                            match coverage {
+                                Coverage::Full => cells.push(child),
+                                Coverage::Partial => candidates.push(child),
+                            }
+                        }
+                    }
+                    None => cells.push(*bits),
+                }
+            }
+        }
+
+        merge_siblings(cells, self.min_precision.significant_bits())
+    }
+}
+
+// The children of bits intersecting the region, or none as soon as more than
+// limit of them do.
+pub(crate) fn children_within<R: Region>(
+    region: &R,
+    bits: &GeohashBits,
+    limit: usize,
+) -> Option<Vec<(GeohashBits, Coverage)>> {
+    let mut children = Vec::new();
+    for child in bits.children() {
+        if let Some(coverage) = region.coverage(&child.bounding_box()) {
+            if children.len() == limit {
+                return None;
+            }
+            children.push((child, coverage));
+        }
+    }
+    Some(children)
+}
+
+// replaces complete groups of siblings by their parent, repeatedly, as long
+// as the parent is not coarser than min_bits
+fn merge_siblings(mut cells: Vec<GeohashBits>, min_bits: u8) -> Vec<GeohashBits> {
+    loop {
+        cells.sort();
+        let mut merged = Vec::with_capacity(cells.len());
+        let mut i = 0;
+        while i < cells.len() {
+            let bits = cells[i];
+            let parent = bits
+                .parent()
+                .filter(|parent| parent.precision().significant_bits() >= min_bits);
+            if let Some(parent) = parent {
+                let siblings = parent.children().count();
+                if i + siblings <= cells.len()
+                    && parent
+                        .children()
+                        .zip(&cells[i..i + siblings])
+                        .all(|(child, cell)| child == *cell)
+                {
+                    merged.push(parent);
+                    i += siblings;
+                    continue;
+                }
+            }
+            merged.push(bits);
+            i += 1;
+        }
+        if merged.len() == cells.len() {
+            return merged;
+        }
+        cells = merged;
+    }
+}
+
+pub fn cover_polygon(polygon: &Polygon, precision: Precision) -> Vec<(GeohashBits, Coverage)> {
+    cover_region(polygon, precision)
+}
+
+// Cells at the given precision intersecting the region, in z-order. Works
+// down from the whole world so only cells on the region boundary are
+// subdivided, cells entirely inside are expanded without further tests.
+pub fn cover_region<R: Region>(region: &R, precision: Precision) -> Vec<(GeohashBits, Coverage)> {
+    if let Err(error) = precision.validate() {
+        panic!("{}", error);
+    }
+    let mut cells = Vec::new();
+    cover_cell(region, GeohashBits::world(), precision, &mut cells);
+    cells
+}
+
+fn cover_cell<R: Region>(
+    region: &R,
+    cell: GeohashBits,
+    precision: Precision,
+    cells: &mut Vec<(GeohashBits, Coverage)>,
+) {
+    match region.coverage(&cell.bounding_box()) {
+        None => {}
+        Some(Coverage::Full) => {
+            cells.extend(
+                cell.descendants(precision)
+                    .map(|bits| (bits, Coverage::Full)),
+            );
+        }
+        Some(Coverage::Partial) => {
+            if cell.precision().significant_bits() == precision.significant_bits() {
+                cells.push((cell, Coverage::Partial));
+            } else {
+                for child in cell.descendants(next_level(cell.precision(), precision)) {
+                    cover_cell(region, child, precision, cells);
+                }
+            }
+        }
+    }
+}
+
+// one bit per axis at a time until the last step reaches the target, which
+// may only add a single bit for odd characters
+pub(crate) fn next_level(current: Precision, target: Precision) -> Precision {
+    if current.significant_bits() + 2 >= target.significant_bits() {
+        target
+    } else {
+        Precision::Bits(current.binary_precision() + 1)
+    }
+}
+
+// same estimate redis uses in geohashEstimateStepsByRadius
+fn estimate_bit_precision(radius_meters: f64, latitude: f64) -> u8 {
+    if radius_meters == 0.0 {
+        return MAX_BINARY_PRECISION;
+    }
+    let mut range = radius_meters;
+    let mut bit_precision: i32 = 1;
+    while range < MERCATOR_MAX {
+        range *= 2.0;
+        bit_precision += 1;
+    }
+    // make sure the range is included in most of the base cases
+    bit_precision -= 2;
+    // cells get narrower towards the poles
+    if !(-66.0..=66.0).contains(&latitude) {
+        bit_precision -= 1;
+        if !(-80.0..=80.0).contains(&latitude) {
+            bit_precision -= 1;
+        }
+    }
+    bit_precision.clamp(1, MAX_BINARY_PRECISION as i32) as u8
+}
+
+// The latitude/longitude extent of a circle in degrees, with longitudes left
+// unwrapped around the center so the extent may go past the 180th meridian.
+struct CircleExtent {
+    center: Location,
+    min_latitude: f64,
+    max_latitude: f64,
+    half_width: f64,
+}
+
+impl CircleExtent {
+    fn new(center: &Location, radius_meters: f64) -> CircleExtent {
+        let angle = radius_meters / EARTH_RADIUS_METERS;
+        let half_height = angle / RADIANS_PER_DEGREE;
+        let min_latitude = center.latitude - half_height;
+        let max_latitude = center.latitude + half_height;
+        let half_width = if min_latitude <= -90.0 || max_latitude >= 90.0 {
+            // the circle contains a pole so it spans every longitude
+            180.0
+        } else {
+            let sin_half_width = angle.sin() / (RADIANS_PER_DEGREE * center.latitude).cos();
+            if sin_half_width >= 1.0 {
+                180.0
+            } else {
+                sin_half_width.asin() / RADIANS_PER_DEGREE
+            }
+        };
+        CircleExtent {
+            center: *center,
+            min_latitude: min_latitude.max(-90.0),
+            max_latitude: max_latitude.min(90.0),
+            half_width,
+        }
+    }
+
+    // whether the block of cell and its neighbors contains the whole extent
+    fn within_neighbors_of(&self, cell: &GeohashBits) -> bool {
+        let bounds = cell.bounding_box();
+        let height = bounds.max().latitude - bounds.min().latitude;
+        let width = bounds.max().longitude - bounds.min().longitude;
+        if self.min_latitude < bounds.min().latitude - height
+            || self.max_latitude > bounds.max().latitude + height
+        {
+            return false;
+        }
+        self.center.longitude - self.half_width >= bounds.min().longitude - width
+            && self.center.longitude + self.half_width <= bounds.max().longitude + width
+    }
+
+    // circles containing a pole span every longitude
+    fn contains_pole(&self) -> bool {
+        self.half_width >= 180.0
+    }
+
+    fn intersects(&self, bounds: &BoundingBox) -> bool {
+        if bounds.max().latitude < self.min_latitude || bounds.min().latitude > self.max_latitude {
+            return false;
+        }
+        let west = self.center.longitude - self.half_width;
+        let east = self.center.longitude + self.half_width;
+        [-360.0, 0.0, 360.0].iter().any(|shift| {
+            bounds.max().longitude + shift >= west && bounds.min().longitude + shift <= east
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merge_siblings;
+    use crate::location::{EARTH_RADIUS_METERS, RADIANS_PER_DEGREE};
+    use crate::{
+        cover_circle, cover_polygon, cover_region, BoundingBox, CoverBuilder, Coverage,
+        GeohashBits, GeohashIterator, Location, Polygon, Precision, Region,
+    };
+    use std::cell::Cell;
+
+    fn destination(from: &Location, bearing_degrees: f64, distance_meters: f64) -> Location {
+        let angle = distance_meters / EARTH_RADIUS_METERS;
+        let bearing = bearing_degrees * RADIANS_PER_DEGREE;
+        let lat = from.latitude * RADIANS_PER_DEGREE;
+        let lon = from.longitude * RADIANS_PER_DEGREE;
+        let to_lat = (lat.sin() * angle.cos() + lat.cos() * angle.sin() * bearing.cos()).asin();
+        let to_lon = lon
+            + (bearing.sin() * angle.sin() * lat.cos())
+                .atan2(angle.cos() - lat.sin() * to_lat.sin());
+        let mut longitude = to_lon / RADIANS_PER_DEGREE;
+        if longitude > 180.0 {
+            longitude -= 360.0;
+        } else if longitude < -180.0 {
+            longitude += 360.0;
+        }
+        Location {
+            longitude,
+            latitude: to_lat / RADIANS_PER_DEGREE,
+        }
+    }
+
+    fn covered(cells: &[GeohashBits], location: &Location) -> bool {
+        cells.iter().any(|cell| {
+            let bounds = cell.bounding_box();
+            bounds.min().latitude <= location.latitude
+                && location.latitude <= bounds.max().latitude
+                && bounds.min().longitude <= location.longitude
+                && location.longitude <= bounds.max().longitude
+        })
+    }
+
+    fn assert_covers(center: Location, radius_meters: f64) -> Vec<GeohashBits> {
+        let cells = cover_circle(&center, radius_meters);
+        assert!(!cells.is_empty() && cells.len() <= 9);
+        assert_contains_circle(&cells, center, radius_meters);
+        cells
+    }
+
+    fn assert_contains_circle(cells: &[GeohashBits], center: Location, radius_meters: f64) {
+        assert!(covered(cells, &center));
+        for fraction in &[0.25, 0.5, 0.75, 0.999] {
+            for bearing in 0..360 {
+                let location = destination(&center, bearing as f64, fraction * radius_meters);
+                assert!(
+                    covered(cells, &location),
+                    "{} {} not covered",
+                    location.longitude,
+                    location.latitude
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_cover_circle() {
+        let center = Location {
+            longitude: -0.1,
+            latitude: 51.5,
+        };
+        for radius in &[1.0, 10.0, 250.0, 5_000.0, 100_000.0, 3_000_000.0] {
+            assert_covers(center, *radius);
+        }
+    }
+
+    #[test]
+    fn test_drops_distant_neighbors() {
+        // a small circle in the middle of a cell needs only that cell
+        let cell = GeohashBits::from_hash("gcpuv");
+        let center = cell.bounding_box().center();
+        let cells = assert_covers(center, 10.0);
+        assert!(cells.len() < 9);
+    }
+
+    #[test]
+    fn test_zero_radius() {
+        let center = Location {
+            longitude: 13.361389,
+            latitude: 38.115556,
+        };
+        let cells = assert_covers(center, 0.0);
+        assert_eq!(cells.len(), 1);
+    }
+
+    fn assert_covers_pole(center: Location, radius_meters: f64) -> Vec<GeohashBits> {
+        let cells = cover_circle(&center, radius_meters);
+        assert_contains_circle(&cells, center, radius_meters);
+        // complete rows of a single precision
+        let precision = cells[0].precision();
+        assert!(cells.iter().all(|bits| bits.precision() == precision));
+        assert_eq!(cells.len() as u64 % cells[0].longitude_cells(), 0);
+        cells
+    }
+
+    fn area_m2(cells: &[GeohashBits]) -> f64 {
+        cells.iter().map(|bits| bits.bounding_box().area_m2()).sum()
+    }
+
+    #[test]
+    fn test_poles() {
+        assert_covers_pole(
+            Location {
+                longitude: 45.0,
+                latitude: 89.9,
+            },
+            50_000.0,
+        );
+        assert_covers(
+            Location {
+                longitude: -120.0,
+                latitude: -89.5,
+            },
+            20_000.0,
+        );
+        let cells = assert_covers_pole(
+            Location {
+                longitude: 0.0,
+                latitude: 90.0,
+            },
+            1_000.0,
+        );
+        assert!(cells
+            .iter()
+            .all(|bits| bits.bounding_box().min().latitude >= 0.0));
+    }
+
+    #[test]
+    fn test_small_polar_circles() {
+        // a single row of cells around the pole, not a whole hemisphere
+        let cells = assert_covers_pole(
+            Location {
+                longitude: 0.0,
+                latitude: 90.0,
+            },
+            1_000.0,
+        );
+        assert!(area_m2(&cells) < 1.0e8);
+        let cells = assert_covers_pole(
+            Location {
+                longitude: 30.0,
+                latitude: 89.99,
+            },
+            2_000.0,
+        );
+        assert!(area_m2(&cells) < 4.0e8);
+        let cells = assert_covers_pole(
+            Location {
+                longitude: 0.0,
+                latitude: -90.0,
+            },
+            0.0,
+        );
+        assert_eq!(cells.len(), 4096);
+    }
+
+    #[test]
+    fn test_antimeridian() {
+        let cells = assert_covers(
+            Location {
+                longitude: 179.999,
+                latitude: -16.5,
+            },
+            1_000.0,
+        );
+        assert!(cells
+            .iter()
+            .any(|bits| bits.bounding_box().min().longitude == -180.0));
+        assert_covers(
+            Location {
+                longitude: -180.0,
+                latitude: 0.0,
+            },
+            75_000.0,
+        );
+    }
+
+    fn location(longitude: f64, latitude: f64) -> Location {
+        Location {
+            longitude,
+            latitude,
+        }
+    }
+
+    fn concave_polygon() -> Polygon {
+        // an L shaped zone with a hole in the corner
+        Polygon::new(
+            vec![
+                location(-0.2, 51.4),
+                location(0.1, 51.4),
+                location(0.1, 51.45),
+                location(-0.15, 51.45),
+                location(-0.15, 51.6),
+                location(-0.2, 51.6),
+            ],
+            vec![vec![
+                location(-0.19, 51.41),
+                location(-0.16, 51.41),
+                location(-0.16, 51.44),
+                location(-0.19, 51.44),
+            ]],
+        )
+    }
+
+    #[test]
+    fn test_cover_polygon_matches_bounding_box_iteration() {
+        let polygon = concave_polygon();
+        let cells = cover_polygon(&polygon, Precision::Bits(14));
+        let expected: Vec<(u64, Coverage)> = GeohashIterator::new(*polygon.bounding_box(), 14)
+            .filter_map(|bits| {
+                polygon
+                    .coverage(&bits.bounding_box())
+                    .map(|coverage| (bits.bits(), coverage))
+            })
+            .collect();
+        let mut actual: Vec<(u64, Coverage)> = cells
+            .iter()
+            .map(|(bits, coverage)| (bits.bits(), *coverage))
+            .collect();
+        let mut expected = expected;
+        expected.sort_by_key(|(bits, _)| *bits);
+        actual.sort_by_key(|(bits, _)| *bits);
+        assert_eq!(actual, expected);
+        assert!(cells
+            .iter()
+            .any(|(_, coverage)| *coverage == Coverage::Full));
+        assert!(cells
+            .windows(2)
+            .all(|pair| pair[0].0.bits() < pair[1].0.bits()));
+    }
+
+    #[test]
+    fn test_cover_polygon_classification() {
+        let polygon = concave_polygon();
+        for (bits, coverage) in cover_polygon(&polygon, Precision::Characters(6)) {
+            assert_eq!(bits.hash().len(), 6);
+            let bounds = bits.bounding_box();
+            let corners = [
+                location(bounds.min().longitude, bounds.min().latitude),
+                location(bounds.min().longitude, bounds.max().latitude),
+                location(bounds.max().longitude, bounds.min().latitude),
+                location(bounds.max().longitude, bounds.max().latitude),
+            ];
+            if coverage == Coverage::Full {
+                assert!(corners.iter().all(|corner| polygon.contains(corner)));
+            }
+            // nothing inside the hole
+            assert!(
+                !(bounds.min().longitude > -0.19
+                    && bounds.max().longitude < -0.16
+                    && bounds.min().latitude > 51.41
+                    && bounds.max().latitude < 51.44)
+            );
+        }
+    }
+
+    #[test]
+    fn test_cover_thin_polygon() {
+        // a thin diagonal sliver only needs a fraction of its bounding box
+        let polygon = Polygon::new(
+            vec![
+                location(-0.2, 51.4),
+                location(0.2, 51.6),
+                location(0.2, 51.601),
+            ],
+            vec![],
+        );
+        let cells = cover_polygon(&polygon, Precision::Bits(16));
+        let bounding_cells = GeohashIterator::new(*polygon.bounding_box(), 16).count();
+        assert!(!cells.is_empty());
+        assert!(cells.len() * 20 < bounding_cells);
+    }
+
+    fn is_covered_by(bits: &GeohashBits, cells: &[GeohashBits]) -> bool {
+        cells.iter().any(|cell| {
+            bits.ancestor(cell.precision())
+                .map(|ancestor| ancestor.bits())
+                == Some(cell.bits())
+        })
+    }
+
+    #[test]
+    fn test_cover_builder_budget() {
+        let polygon = concave_polygon();
+        let fine = cover_polygon(&polygon, Precision::Bits(18));
+        for max_cells in &[4, 16, 64] {
+            let cells = CoverBuilder::new()
+                .min_precision(Precision::Bits(1))
+                .max_precision(Precision::Bits(18))
+                .max_cells(*max_cells)
+                .cover(&polygon);
+            assert!(cells.len() <= *max_cells);
+            assert!(fine.iter().all(|(bits, _)| is_covered_by(bits, &cells)));
+        }
+
+        let cells = CoverBuilder::new()
+            .max_precision(Precision::Bits(18))
+            .max_cells(200)
+            .cover(&polygon);
+        let precisions: Vec<u8> = cells
+            .iter()
+            .map(|bits| bits.precision().binary_precision())
+            .collect();
+        assert!(precisions.iter().min() < precisions.iter().max());
+        assert!(cells.len() < fine.len());
+        assert!(cells.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_cover_builder_min_precision() {
+        let polygon = concave_polygon();
+        let cells = CoverBuilder::new()
+            .min_precision(Precision::Bits(12))
+            .max_precision(Precision::Bits(14))
+            .max_cells(1)
+            .cover(&polygon);
+        assert!(cells.len() > 1);
+        assert!(cells
+            .iter()
+            .all(|bits| bits.precision().binary_precision() == 12));
+    }
+
+    struct CountingRegion<'a, R> {
+        region: &'a R,
+        calls: Cell<usize>,
+    }
+
+    impl<'a, R: Region> Region for CountingRegion<'a, R> {
+        fn coverage(&self, cell: &BoundingBox) -> Option<Coverage> {
+            self.calls.set(self.calls.get() + 1);
+            self.region.coverage(cell)
+        }
+    }
+
+    #[test]
+    fn test_cover_builder_skips_candidates_over_budget() {
+        let polygon = concave_polygon();
+        let region = CountingRegion {
+            region: &polygon,
+            calls: Cell::new(0),
+        };
+        cover_region(&region, Precision::Bits(12));
+        let min_precision_calls = region.calls.replace(0);
+        // no cell fits the budget, so no children are looked at
+        CoverBuilder::new()
+            .min_precision(Precision::Bits(12))
+            .max_precision(Precision::Bits(14))
+            .max_cells(1)
+            .cover(&region);
+        assert_eq!(region.calls.get(), min_precision_calls);
+    }
+
+    #[test]
+    fn test_cover_builder_characters() {
+        let bounds = GeohashBits::from_hash("dp3").bounding_box();
+        let cells = CoverBuilder::new()
+            .min_precision(Precision::Characters(2))
+            .max_precision(Precision::Characters(5))
+            .max_cells(1000)
+            .cover(&bounds);
+        assert!(cells.iter().any(|bits| bits.hash() == "dp3"));
+        assert!(cells
+            .iter()
+            .all(|bits| (2..=5).contains(&bits.hash().len())));
+        // far fewer than the 131841 cells of the fixed precision iteration
+        assert!(cells.len() < 1000);
+    }
+
+    #[test]
+    #[should_panic(expected = "precision out of range")]
+    fn test_cover_region_precision_out_of_range() {
+        let bounds = GeohashBits::from_hash("gcpu").bounding_box();
+        cover_region(&bounds, Precision::Characters(60));
+    }
+
+    #[test]
+    fn test_merge_siblings() {
+        let parent = GeohashBits::from_hash("dp3");
+        let merged = merge_siblings(parent.children().collect(), 5);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].hash(), "dp3");
+
+        // never coarser than the minimum
+        let merged = merge_siblings(parent.children().collect(), 20);
+        assert_eq!(merged.len(), 32);
+
+        let parent = GeohashBits::from_location(&location(-0.1, 51.5), Precision::Bits(10));
+        let mut cells: Vec<GeohashBits> = parent
+            .children()
+            .flat_map(|child| child.children().collect::<Vec<_>>())
+            .collect();
+        cells.reverse();
+        let merged = merge_siblings(cells, 2);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].bits(), parent.bits());
+
+        let mut partial: Vec<GeohashBits> = parent.children().collect();
+        partial.pop();
+        assert_eq!(merge_siblings(partial, 2).len(), 3);
+    }
+}
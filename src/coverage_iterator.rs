@@ -0,0 +1,79 @@
+use crate::*;
+
+// The cells of a GeohashIterator classified against its bounds: Full cells
+// lie entirely inside, Partial cells only overlap the bounds.
+pub struct CoverageIterator {
+    cells: GeohashIterator,
+}
+
+impl CoverageIterator {
+    pub fn new(bounds: BoundingBox, precision: Precision) -> CoverageIterator {
+        GeohashIterator::with_precision(bounds, precision).with_coverage()
+    }
+
+    pub(crate) fn from_cells(cells: GeohashIterator) -> CoverageIterator {
+        CoverageIterator { cells }
+    }
+}
+
+impl std::iter::Iterator for CoverageIterator {
+    type Item = (GeohashBits, Coverage);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bits = self.cells.next()?;
+        let coverage = if self.cells.bounds().contains_box(&bits.bounding_box()) {
+            Coverage::Full
+        } else {
+            Coverage::Partial
+        };
+        Some((bits, coverage))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BoundingBox, Coverage, CoverageIterator, GeohashBits, Location, Precision};
+
+    #[test]
+    fn test_coverage() {
+        let bounds = GeohashBits::from_hash("gcpu").bounding_box();
+        let cells: Vec<(GeohashBits, Coverage)> =
+            CoverageIterator::new(bounds, Precision::Characters(5)).collect();
+        assert_eq!(cells.len(), 45);
+        for (bits, coverage) in cells {
+            let inside = bits.hash().starts_with("gcpu");
+            assert_eq!(coverage == Coverage::Full, inside);
+        }
+    }
+
+    #[test]
+    fn test_partial() {
+        let bounds = BoundingBox::new(
+            &Location {
+                longitude: -0.1,
+                latitude: 51.5,
+            },
+            &Location {
+                longitude: -0.05,
+                latitude: 51.52,
+            },
+        );
+        let cells: Vec<(GeohashBits, Coverage)> =
+            CoverageIterator::new(bounds, Precision::Characters(6)).collect();
+        let full = cells
+            .iter()
+            .filter(|(_, coverage)| *coverage == Coverage::Full)
+            .count();
+        assert!(full > 0);
+        assert!(full < cells.len());
+        for (bits, coverage) in cells {
+            let cell = bits.bounding_box();
+            assert!(bounds.intersects(&cell));
+            assert_eq!(coverage == Coverage::Full, bounds.contains_box(&cell));
+            // cells on the edge of the bounds are never entirely inside
+            if cell.min().latitude < 51.5 || cell.max().longitude > -0.05 {
+                assert_eq!(coverage, Coverage::Partial);
+            }
+        }
+    }
+}
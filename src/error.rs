@@ -0,0 +1,55 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GeohashError {
+    InvalidCharacter { character: char, position: usize },
+    HashTooLong(usize),
+    BitsOutOfRange(u64),
+    PrecisionOutOfRange(u8),
+    LatitudeOutOfRange(f64),
+    LongitudeOutOfRange(f64),
+    DistanceOutOfRange(f64),
+    NaN,
+    EmptyPolygon,
+}
+
+impl fmt::Display for GeohashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GeohashError::InvalidCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "invalid geohash character {:?} at position {}",
+                character, position
+            ),
+            GeohashError::HashTooLong(length) => write!(
+                f,
+                "geohash of length {} is too long, at most {} characters are supported",
+                length,
+                crate::geohash_bits::MAX_CHARACTER_PRECISION
+            ),
+            GeohashError::BitsOutOfRange(bits) => {
+                write!(f, "bits {:#x} out of range for the precision", bits)
+            }
+            GeohashError::PrecisionOutOfRange(bits) => {
+                write!(f, "precision out of range: {} bits per axis", bits)
+            }
+            GeohashError::LatitudeOutOfRange(latitude) => {
+                write!(f, "latitude out of range: {}", latitude)
+            }
+            GeohashError::LongitudeOutOfRange(longitude) => {
+                write!(f, "longitude out of range: {}", longitude)
+            }
+            GeohashError::DistanceOutOfRange(meters) => {
+                write!(f, "distance out of range: {} meters", meters)
+            }
+            GeohashError::NaN => write!(f, "coordinate is NaN"),
+            GeohashError::EmptyPolygon => write!(f, "polygon has no points"),
+        }
+    }
+}
+
+impl Error for GeohashError {}
@@ -0,0 +1,222 @@
+// Conversions to and from geo-types, enabled with the `geo-types` feature.
+// geo-types geometries use x for longitude and y for latitude. Rect,
+// Polygon and MultiPolygon are regions so they can be covered directly.
+use crate::polygon::edges_coverage;
+use crate::*;
+use std::convert::TryFrom;
+
+fn location(coord: geo_types::Coord<f64>) -> Location {
+    Location {
+        longitude: coord.x,
+        latitude: coord.y,
+    }
+}
+
+fn coord(location: Location) -> geo_types::Coord<f64> {
+    geo_types::Coord {
+        x: location.longitude,
+        y: location.latitude,
+    }
+}
+
+// a rect extending past 180, as converted from a box crossing the 180th
+// meridian, wraps back into one
+fn bounding_box(rect: &geo_types::Rect<f64>) -> BoundingBox {
+    let min = location(rect.min());
+    let mut max = location(rect.max());
+    if max.longitude > 180.0 && max.longitude - 360.0 < min.longitude {
+        max.longitude -= 360.0;
+    }
+    BoundingBox { min, max }
+}
+
+impl From<Location> for geo_types::Point<f64> {
+    fn from(location: Location) -> Self {
+        geo_types::Point::new(location.longitude, location.latitude)
+    }
+}
+
+impl TryFrom<geo_types::Point<f64>> for Location {
+    type Error = GeohashError;
+
+    fn try_from(point: geo_types::Point<f64>) -> Result<Self, Self::Error> {
+        Location::try_new(point.x(), point.y())
+    }
+}
+
+impl From<BoundingBox> for geo_types::Rect<f64> {
+    // a box crossing the 180th meridian extends past 180 instead
+    fn from(bbox: BoundingBox) -> Self {
+        let mut max = coord(bbox.max);
+        if bbox.crosses_antimeridian() {
+            max.x += 360.0;
+        }
+        geo_types::Rect::new(coord(bbox.min), max)
+    }
+}
+
+impl TryFrom<geo_types::Rect<f64>> for BoundingBox {
+    type Error = GeohashError;
+
+    fn try_from(rect: geo_types::Rect<f64>) -> Result<Self, Self::Error> {
+        let bbox = bounding_box(&rect);
+        bbox.min.validate()?;
+        bbox.max.validate()?;
+        Ok(bbox)
+    }
+}
+
+impl From<GeohashBits> for geo_types::Polygon<f64> {
+    fn from(bits: GeohashBits) -> Self {
+        geo_types::Rect::from(bits.bounding_box()).to_polygon()
+    }
+}
+
+impl From<&Polygon> for geo_types::Polygon<f64> {
+    fn from(polygon: &Polygon) -> Self {
+        let ring = |ring: &[Location]| -> geo_types::LineString<f64> {
+            ring.iter().cloned().map(coord).collect()
+        };
+        geo_types::Polygon::new(
+            ring(polygon.exterior()),
+            polygon.holes().iter().map(|hole| ring(hole)).collect(),
+        )
+    }
+}
+
+impl TryFrom<&geo_types::Polygon<f64>> for Polygon {
+    type Error = GeohashError;
+
+    fn try_from(polygon: &geo_types::Polygon<f64>) -> Result<Self, Self::Error> {
+        let ring =
+            |ring: &geo_types::LineString<f64>| ring.coords().cloned().map(location).collect();
+        Polygon::try_new(
+            ring(polygon.exterior()),
+            polygon.interiors().iter().map(ring).collect(),
+        )
+    }
+}
+
+impl Region for geo_types::Rect<f64> {
+    fn coverage(&self, cell: &BoundingBox) -> Option<Coverage> {
+        bounding_box(self).coverage(cell)
+    }
+}
+
+impl Region for geo_types::Polygon<f64> {
+    fn coverage(&self, cell: &BoundingBox) -> Option<Coverage> {
+        // geo-types closes rings so consecutive coordinates are the edges
+        edges_coverage(
+            || {
+                std::iter::once(self.exterior())
+                    .chain(self.interiors())
+                    .flat_map(|ring| ring.lines())
+                    .map(|line| (location(line.start), location(line.end)))
+            },
+            cell,
+        )
+    }
+}
+
+impl Region for geo_types::MultiPolygon<f64> {
+    fn coverage(&self, cell: &BoundingBox) -> Option<Coverage> {
+        let mut coverage = None;
+        for polygon in self {
+            match polygon.coverage(cell) {
+                Some(Coverage::Full) => return Some(Coverage::Full),
+                Some(Coverage::Partial) => coverage = Some(Coverage::Partial),
+                None => {}
+            }
+        }
+        coverage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cover_polygon, cover_region, BoundingBox, CoverBuilder, GeohashBits, GeohashError,
+    };
+    use crate::{Location, Polygon, Precision};
+    use geo_types::{polygon, Coord, MultiPolygon, Point, Rect};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_point() {
+        let location = Location {
+            longitude: -0.1,
+            latitude: 51.5,
+        };
+        let point = Point::from(location);
+        assert_eq!(point, Point::new(-0.1, 51.5));
+        assert_eq!(Location::try_from(point), Ok(location));
+        assert_eq!(
+            Location::try_from(Point::new(0.0, 95.0)),
+            Err(GeohashError::LatitudeOutOfRange(95.0))
+        );
+    }
+
+    #[test]
+    fn test_rect() {
+        let bbox = GeohashBits::from_hash("gcpuv").bounding_box();
+        let rect = Rect::from(bbox);
+        assert_eq!(rect.min().x, bbox.min().longitude);
+        assert_eq!(rect.max().y, bbox.max().latitude);
+        assert_eq!(BoundingBox::try_from(rect), Ok(bbox));
+        assert!(BoundingBox::try_from(Rect::new(
+            Coord { x: 0.0, y: 0.0 },
+            Coord { x: 370.0, y: 1.0 }
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_rect_antimeridian() {
+        let bbox = BoundingBox::new(
+            &Location {
+                longitude: 170.0,
+                latitude: -20.0,
+            },
+            &Location {
+                longitude: -175.0,
+                latitude: -10.0,
+            },
+        );
+        let rect = Rect::from(bbox);
+        assert_eq!(rect.min().x, 170.0);
+        assert_eq!(rect.max().x, 185.0);
+        assert_eq!(BoundingBox::try_from(rect), Ok(bbox));
+
+        let builder = CoverBuilder::new().max_precision(Precision::Bits(10));
+        assert_eq!(builder.cover(&rect), builder.cover(&bbox));
+    }
+
+    #[test]
+    fn test_cell_polygon() {
+        let bits = GeohashBits::from_hash("gcpuv");
+        let polygon = geo_types::Polygon::from(bits);
+        assert_eq!(polygon.exterior().coords().count(), 5);
+        let cell = Polygon::try_from(&polygon).unwrap();
+        assert_eq!(*cell.bounding_box(), bits.bounding_box());
+        assert_eq!(geo_types::Polygon::from(&cell), polygon);
+    }
+
+    #[test]
+    fn test_cover() {
+        let triangle = polygon![
+            (x: 0.0, y: 0.0),
+            (x: 10.0, y: 0.0),
+            (x: 0.0, y: 10.0),
+        ];
+        let expected = cover_polygon(&Polygon::try_from(&triangle).unwrap(), Precision::Bits(8));
+        assert_eq!(cover_region(&triangle, Precision::Bits(8)), expected);
+
+        let multi = MultiPolygon::new(vec![triangle.clone()]);
+        assert_eq!(cover_region(&multi, Precision::Bits(8)), expected);
+
+        let rect = Rect::new(Coord { x: 0.0, y: 0.0 }, Coord { x: 10.0, y: 10.0 });
+        let bbox = BoundingBox::try_from(rect).unwrap();
+        let builder = CoverBuilder::new().max_precision(Precision::Bits(10));
+        assert_eq!(builder.cover(&rect), builder.cover(&bbox));
+    }
+}
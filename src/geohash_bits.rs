@@ -1,103 +1,63 @@
+use crate::location::{EARTH_RADIUS_METERS, RADIANS_PER_DEGREE};
+use crate::ops;
 use crate::*;
-use lazy_static::lazy_static;
-use std::collections::HashMap;
+use std::cmp::Ordering;
 use std::f64;
+use std::fmt;
+use std::str::FromStr;
 
-#[cfg(feature = "simd")]
-mod ops {
-    use packed_simd::u64x2;
-
-    const ALTERNATING_1: u64x2 = u64x2::new(0x5555555555555555, 0x5555555555555555);
-    const ALTERNATING_2: u64x2 = u64x2::new(0x3333333333333333, 0x3333333333333333);
-    const ALTERNATING_4: u64x2 = u64x2::new(0x0F0F0F0F0F0F0F0F, 0x0F0F0F0F0F0F0F0F);
-    const ALTERNATING_8: u64x2 = u64x2::new(0x00FF00FF00FF00FF, 0x00FF00FF00FF00FF);
-    const ALTERNATING_16: u64x2 = u64x2::new(0x0000FFFF0000FFFF, 0x0000FFFF0000FFFF);
-    const ALTERNATING_32: u64x2 = u64x2::new(0x00000000FFFFFFFF, 0x00000000FFFFFFFF);
-
-    pub fn interleave_bits(even_bits: u32, odd_bits: u32) -> u64 {
-        let mut bits = u64x2::new(even_bits as u64, odd_bits as u64);
-
-        bits = (bits | (bits << 16)) & ALTERNATING_16;
-        bits = (bits | (bits << 8)) & ALTERNATING_8;
-        bits = (bits | (bits << 4)) & ALTERNATING_4;
-        bits = (bits | (bits << 2)) & ALTERNATING_2;
-        bits = (bits | (bits << 1)) & ALTERNATING_1;
-
-        bits.extract(0) | (bits.extract(1) << 1)
-    }
-
-    pub fn deinterleave_bits(interleaved: u64) -> (u32, u32) {
-        let mut bits = u64x2::new(interleaved, interleaved >> 1) & ALTERNATING_1;
-
-        bits = (bits | (bits >> 1)) & ALTERNATING_2;
-        bits = (bits | (bits >> 2)) & ALTERNATING_4;
-        bits = (bits | (bits >> 4)) & ALTERNATING_8;
-        bits = (bits | (bits >> 8)) & ALTERNATING_16;
-        bits = (bits | (bits >> 16)) & ALTERNATING_32;
-
-        (bits.extract(0) as u32, bits.extract(1) as u32)
-    }
-}
-
-#[cfg(not(feature = "simd"))]
-mod ops {
-    pub fn interleave_bits(even_bits: u32, odd_bits: u32) -> u64 {
-        let mut e = even_bits as u64;
-        let mut o = odd_bits as u64;
-
-        e = (e | (e << 16)) & 0x0000FFFF0000FFFF;
-        o = (o | (o << 16)) & 0x0000FFFF0000FFFF;
-
-        e = (e | (e << 8)) & 0x00FF00FF00FF00FF;
-        o = (o | (o << 8)) & 0x00FF00FF00FF00FF;
-
-        e = (e | (e << 4)) & 0x0F0F0F0F0F0F0F0F;
-        o = (o | (o << 4)) & 0x0F0F0F0F0F0F0F0F;
-
-        e = (e | (e << 2)) & 0x3333333333333333;
-        o = (o | (o << 2)) & 0x3333333333333333;
-
-        e = (e | (e << 1)) & 0x5555555555555555;
-        o = (o | (o << 1)) & 0x5555555555555555;
-
-        e | (o << 1)
-    }
-
-    pub fn deinterleave_bits(interleaved: u64) -> (u32, u32) {
-        let mut e = interleaved & 0x5555555555555555;
-        let mut o = (interleaved >> 1) & 0x5555555555555555;
-
-        e = (e | (e >> 1)) & 0x3333333333333333;
-        o = (o | (o >> 1)) & 0x3333333333333333;
-
-        e = (e | (e >> 2)) & 0x0F0F0F0F0F0F0F0F;
-        o = (o | (o >> 2)) & 0x0F0F0F0F0F0F0F0F;
-
-        e = (e | (e >> 4)) & 0x00FF00FF00FF00FF;
-        o = (o | (o >> 4)) & 0x00FF00FF00FF00FF;
-
-        e = (e | (e >> 8)) & 0x0000FFFF0000FFFF;
-        o = (o | (o >> 8)) & 0x0000FFFF0000FFFF;
-
-        e = (e | (e >> 16)) & 0x00000000FFFFFFFF;
-        o = (o | (o >> 16)) & 0x00000000FFFFFFFF;
-
-        (e as u32, o as u32)
-    }
-}
-
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "crate::serialization::RawPrecision")
+)]
 pub enum Precision {
     Bits(u8),
     Characters(u8),
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "crate::serialization::RawGeohashBits")
+)]
 pub struct GeohashBits {
     bits: u64,
     precision: Precision,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Neighbors {
+    pub center: GeohashBits,
+    pub north: Option<GeohashBits>,
+    pub north_east: Option<GeohashBits>,
+    pub east: GeohashBits,
+    pub south_east: Option<GeohashBits>,
+    pub south: Option<GeohashBits>,
+    pub south_west: Option<GeohashBits>,
+    pub west: GeohashBits,
+    pub north_west: Option<GeohashBits>,
+}
+
+impl Neighbors {
+    // row by row from the north west corner, rows beyond a pole are None
+    pub fn to_array(&self) -> [Option<GeohashBits>; 9] {
+        [
+            self.north_west,
+            self.north,
+            self.north_east,
+            Some(self.west),
+            Some(self.center),
+            Some(self.east),
+            self.south_west,
+            self.south,
+            self.south_east,
+        ]
+    }
+}
+
 #[derive(PartialEq)]
 enum InterleaveSet {
     Odds,
@@ -105,55 +65,160 @@ enum InterleaveSet {
 }
 
 impl Precision {
+    // saturates at u8::MAX for character counts far out of range
     pub fn binary_precision(&self) -> u8 {
-        match self {
-            &Precision::Bits(n) => n,
-            &Precision::Characters(n) => (0.5 * (5 * n) as f32).ceil() as u8,
+        match *self {
+            Precision::Bits(n) => n,
+            Precision::Characters(n) => (5 * n as u16).div_ceil(2).min(u8::MAX as u16) as u8,
         }
     }
 
     pub fn character_precision(&self) -> u8 {
-        match self {
-            &Precision::Bits(n) => (0.4 * n as f64) as u8,
-            &Precision::Characters(n) => n,
+        match *self {
+            Precision::Bits(n) => (0.4 * n as f64) as u8,
+            Precision::Characters(n) => n,
         }
     }
 
     pub fn max_binary_value(&self) -> f64 {
-        (1 << self.binary_precision() as u64) as f64
+        (1u64 << self.binary_precision()) as f64
     }
 
     pub fn is_odd_characters(&self) -> bool {
-        match self {
-            &Precision::Bits(_) => false,
-            &Precision::Characters(n) => (n % 2) > 0,
+        match *self {
+            Precision::Bits(_) => false,
+            Precision::Characters(n) => (n % 2) > 0,
+        }
+    }
+
+    // The coarsest precision locating any point within meters of the center
+    // of its cell along each axis, at the given latitude. Always a
+    // Precision::Bits, Bits(32) when no precision is fine enough.
+    pub fn for_error_meters(meters: f64, latitude: f64) -> Result<Precision, GeohashError> {
+        Precision::for_cell_size(2.0 * meters, 2.0 * meters, latitude)
+    }
+
+    // The coarsest precision whose cells are no wider and no taller than
+    // the given size at the given latitude. Always a Precision::Bits,
+    // Bits(32) when no precision is fine enough.
+    pub fn for_cell_size(
+        width_meters: f64,
+        height_meters: f64,
+        latitude: f64,
+    ) -> Result<Precision, GeohashError> {
+        for &meters in &[width_meters, height_meters] {
+            if meters.is_nan() || meters <= 0.0 {
+                return Err(GeohashError::DistanceOutOfRange(meters));
+            }
+        }
+        if latitude.is_nan() {
+            return Err(GeohashError::NaN);
+        }
+        if !LATITUDE_RANGE.contains(&latitude) {
+            return Err(GeohashError::LatitudeOutOfRange(latitude));
+        }
+        let meters_per_degree = EARTH_RADIUS_METERS * RADIANS_PER_DEGREE;
+        let parallel_scale = (RADIANS_PER_DEGREE * latitude).cos();
+        Ok((1..MAX_BINARY_PRECISION)
+            .map(Precision::Bits)
+            .find(|precision| {
+                let (lon_degrees, lat_degrees) = precision.cell_degrees();
+                lon_degrees * meters_per_degree * parallel_scale <= width_meters
+                    && lat_degrees * meters_per_degree <= height_meters
+            })
+            .unwrap_or(Precision::Bits(MAX_BINARY_PRECISION)))
+    }
+
+    // longitude and latitude extent of a cell, odd character counts have
+    // one less latitude bit
+    pub(crate) fn cell_degrees(&self) -> (f64, f64) {
+        let max_binary_value = self.max_binary_value();
+        let lat_max_binary_value = if self.is_odd_characters() {
+            0.5 * max_binary_value
+        } else {
+            max_binary_value
+        };
+        (
+            (LONGITUDE_RANGE.end() - LONGITUDE_RANGE.start()) / max_binary_value,
+            (LATITUDE_RANGE.end() - LATITUDE_RANGE.start()) / lat_max_binary_value,
+        )
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), GeohashError> {
+        let in_range = match *self {
+            Precision::Bits(n) => (1..=MAX_BINARY_PRECISION).contains(&n),
+            Precision::Characters(n) => (1..=MAX_CHARACTER_PRECISION as u8).contains(&n),
+        };
+        if in_range {
+            Ok(())
+        } else {
+            Err(GeohashError::PrecisionOutOfRange(self.binary_precision()))
+        }
+    }
+
+    // bits of the interleaved value that are actually part of the hash,
+    // saturating so out of range precisions are finer than any valid one
+    pub(crate) fn significant_bits(&self) -> u8 {
+        match *self {
+            Precision::Bits(n) => n.saturating_mul(2),
+            Precision::Characters(n) => n.saturating_mul(5),
+        }
+    }
+
+    // one level of the hierarchy, a bit per axis or a character
+    pub(crate) fn finer(&self) -> Precision {
+        match *self {
+            Precision::Bits(n) => Precision::Bits(n + 1),
+            Precision::Characters(n) => Precision::Characters(n + 1),
+        }
+    }
+
+    pub(crate) fn coarser(&self) -> Precision {
+        match *self {
+            Precision::Bits(n) => Precision::Bits(n - 1),
+            Precision::Characters(n) => Precision::Characters(n - 1),
         }
     }
+
+    fn padding_bits(&self) -> u8 {
+        2 * self.binary_precision() - self.significant_bits()
+    }
+
+    // odd characters leave the lowest latitude bit out of the hash
+    pub(crate) fn padding_mask(&self) -> u64 {
+        !((1u64 << self.padding_bits()) - 1)
+    }
 }
 
-const MAX_BINARY_PRECISION: u8 = 32;
+pub(crate) const MAX_BINARY_PRECISION: u8 = 32;
+pub(crate) const MAX_CHARACTER_PRECISION: usize = 12;
 
-fn float_to_bits(value: f64, range: &LocationRange, max_binary_value: f64) -> u32 {
+pub(crate) fn float_to_bits(value: f64, range: &LocationRange, max_binary_value: f64) -> u32 {
     let fraction = (value - *range.start()) / (range.end() - range.start());
-    (fraction * max_binary_value) as u32
+    // the end of the range belongs to the last cell
+    (fraction * max_binary_value).min(max_binary_value - 1.0) as u32
 }
 
-fn bits_to_float(bits: u32, range: &LocationRange, max_binary_value: f64) -> f64 {
+pub(crate) fn bits_to_float(bits: u32, range: &LocationRange, max_binary_value: f64) -> f64 {
     let fraction = (bits as f64) / max_binary_value;
     *range.start() + fraction * (range.end() - range.start())
 }
 
 const BASE32_CHARACTERS: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
 
-lazy_static! {
-    static ref BASE32_BITS: HashMap<char, u64> = {
-        let mut map = HashMap::new();
-        for (i, c) in BASE32_CHARACTERS.iter().enumerate() {
-            map.insert(char::from(*c), i as u64);
-        }
-        map
-    };
-}
+// value of each base32 byte in either case, INVALID_BASE32 for the rest
+const INVALID_BASE32: u8 = 0xff;
+const BASE32_BITS: [u8; 256] = {
+    let mut table = [INVALID_BASE32; 256];
+    let mut i = 0;
+    while i < BASE32_CHARACTERS.len() {
+        let c = BASE32_CHARACTERS[i];
+        table[c as usize] = i as u8;
+        table[c.to_ascii_uppercase() as usize] = i as u8;
+        i += 1;
+    }
+    table
+};
 
 impl InterleaveSet {
     pub fn modify_mask(&self) -> u64 {
@@ -169,55 +234,284 @@ impl InterleaveSet {
             InterleaveSet::Odds => 0x5555555555555555,
         }
     }
+
+    pub fn spread(&self, value: u32) -> u64 {
+        match self {
+            InterleaveSet::Evens => ops::interleave_bits(value, 0),
+            InterleaveSet::Odds => ops::interleave_bits(0, value),
+        }
+    }
 }
 
 impl GeohashBits {
     pub fn from_location(location: &Location, precision: Precision) -> GeohashBits {
-        location.validate_range();
-        let binary_precision = precision.binary_precision();
-        assert!(
-            (1..=MAX_BINARY_PRECISION).contains(&binary_precision),
-            "precision out of range"
-        );
+        match GeohashBits::try_from_location(location, precision) {
+            Ok(bits) => bits,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    pub fn try_from_location(
+        location: &Location,
+        precision: Precision,
+    ) -> Result<GeohashBits, GeohashError> {
+        location.validate()?;
+        precision.validate()?;
         let max_binary_value = precision.max_binary_value();
 
         let longitude_bits = float_to_bits(location.longitude, &LONGITUDE_RANGE, max_binary_value);
         let latitude_bits = float_to_bits(location.latitude, &LATITUDE_RANGE, max_binary_value);
 
-        GeohashBits {
-            bits: ops::interleave_bits(latitude_bits, longitude_bits),
+        Ok(GeohashBits {
+            bits: ops::interleave_bits(latitude_bits, longitude_bits) & precision.padding_mask(),
             precision,
+        })
+    }
+
+    pub fn from_bits(bits: u64, precision: Precision) -> GeohashBits {
+        match GeohashBits::try_from_bits(bits, precision) {
+            Ok(bits) => bits,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    // the inverse of bits(), rejecting bits outside the precision
+    pub fn try_from_bits(bits: u64, precision: Precision) -> Result<GeohashBits, GeohashError> {
+        precision.validate()?;
+        let significant_mask =
+            (u64::MAX >> (64 - precision.significant_bits())) << precision.padding_bits();
+        if bits & !significant_mask != 0 {
+            return Err(GeohashError::BitsOutOfRange(bits));
         }
+        Ok(GeohashBits { bits, precision })
     }
 
     pub fn from_hash(hash: &str) -> GeohashBits {
-        let total_bit_length = 2 * (0.5 * 5.0 * hash.len() as f64).ceil() as u64;
+        match GeohashBits::try_from_hash(hash) {
+            Ok(bits) => bits,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    pub fn try_from_hash(hash: &str) -> Result<GeohashBits, GeohashError> {
+        if !hash.is_ascii() {
+            // never valid, but report lengths and positions in characters
+            // rather than in bytes
+            let length = hash.chars().count();
+            if length > MAX_CHARACTER_PRECISION {
+                return Err(GeohashError::HashTooLong(length));
+            }
+            let (position, character) = hash
+                .chars()
+                .enumerate()
+                .find(|(_, c)| !c.is_ascii() || BASE32_BITS[*c as usize] == INVALID_BASE32)
+                .unwrap();
+            return Err(GeohashError::InvalidCharacter {
+                character,
+                position,
+            });
+        }
+        GeohashBits::try_from_hash_bytes(hash.as_bytes())
+    }
+
+    pub fn from_hash_bytes(hash: &[u8]) -> GeohashBits {
+        match GeohashBits::try_from_hash_bytes(hash) {
+            Ok(bits) => bits,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    // base32 in either case, an invalid byte is reported as the character
+    // with that code point
+    pub fn try_from_hash_bytes(hash: &[u8]) -> Result<GeohashBits, GeohashError> {
+        if hash.len() > MAX_CHARACTER_PRECISION {
+            return Err(GeohashError::HashTooLong(hash.len()));
+        }
+        if hash.is_empty() {
+            return Err(GeohashError::PrecisionOutOfRange(0));
+        }
+        let total_bit_length = 2 * (5 * hash.len() as u64).div_ceil(2);
         let mut bits: u64 = 0;
-        for (i, c) in hash.chars().enumerate() {
-            bits |= BASE32_BITS[&c] << (total_bit_length - 5 * (i as u64 + 1));
+        for (i, &byte) in hash.iter().enumerate() {
+            let value = BASE32_BITS[byte as usize];
+            if value == INVALID_BASE32 {
+                return Err(GeohashError::InvalidCharacter {
+                    character: char::from(byte),
+                    position: i,
+                });
+            }
+            bits |= (value as u64) << (total_bit_length - 5 * (i as u64 + 1));
         }
-        GeohashBits {
+        Ok(GeohashBits {
             bits,
             precision: Precision::Characters(hash.len() as u8),
-        }
+        })
     }
 
     pub fn hash(&self) -> String {
+        self.hash_inline().to_string()
+    }
+
+    pub fn hash_inline(&self) -> GeohashStr {
         let character_precision = self.precision.character_precision();
         let total_binary_precision = 2 * self.precision.binary_precision();
-        let mut hash = String::with_capacity(character_precision as usize);
+        let mut hash = GeohashStr::new();
         for i in 1..=character_precision {
             // each character is 5 bits
             let index = (self.bits >> (total_binary_precision - i * 5) as u64) & 0x1f;
-            hash.push(char::from(BASE32_CHARACTERS[index as usize]));
+            hash.push(BASE32_CHARACTERS[index as usize]);
         }
         hash
     }
 
+    pub fn write_hash<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        out.write_str(&self.hash_inline())
+    }
+
     pub fn bits(&self) -> u64 {
         self.bits
     }
 
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    // the largest difference in degrees between a location and the center
+    // of its cell
+    pub fn lat_error(&self) -> f64 {
+        0.5 * self.precision.cell_degrees().1
+    }
+
+    pub fn lon_error(&self) -> f64 {
+        0.5 * self.precision.cell_degrees().0
+    }
+
+    // width and height, the width measured along the parallel through the
+    // center of the cell
+    pub fn cell_size_meters(&self) -> (f64, f64) {
+        let bounds = self.bounding_box();
+        (bounds.width_meters(), bounds.height_meters())
+    }
+
+    // the zero bit cell spanning the whole world, only useful to subdivide
+    pub(crate) fn world() -> GeohashBits {
+        GeohashBits {
+            bits: 0,
+            precision: Precision::Bits(0),
+        }
+    }
+
+    // every cell at the finer precision inside this one, in z-order
+    pub(crate) fn descendants(&self, precision: Precision) -> impl Iterator<Item = GeohashBits> {
+        let extra_bits = precision.significant_bits() - self.precision.significant_bits();
+        let prefix = (self.bits >> self.precision.padding_bits()) << extra_bits;
+        let padding_bits = precision.padding_bits();
+        (0..1u64 << extra_bits).map(move |suffix| GeohashBits {
+            bits: (prefix | suffix) << padding_bits,
+            precision,
+        })
+    }
+
+    // 4 cells per bit of precision, 32 per character, none at the maximum precision
+    pub fn children(&self) -> impl Iterator<Item = GeohashBits> {
+        let mut precision = self.precision.finer();
+        let mut count = usize::MAX;
+        if precision.binary_precision() > MAX_BINARY_PRECISION {
+            precision = self.precision;
+            count = 0;
+        }
+        self.descendants(precision).take(count)
+    }
+
+    pub fn parent(&self) -> Option<GeohashBits> {
+        self.ancestor(self.precision.coarser())
+    }
+
+    // the cell containing this one at a coarser (or the same) precision,
+    // the precision kind may differ, e.g. Precision::Bits(10) of "u10hfr2c" is "u10h"
+    pub fn ancestor(&self, precision: Precision) -> Option<GeohashBits> {
+        if precision.binary_precision() == 0
+            || precision.significant_bits() > self.precision.significant_bits()
+        {
+            return None;
+        }
+        Some(self.truncated(precision))
+    }
+
+    // Converts explicitly instead of reinterpreting the bits. A coarser
+    // precision truncates to the cell containing this one, a finer one pads
+    // with zeros to the cell at this one's south west corner. Switching kinds
+    // is usually one or the other, e.g. Bits(26) to Characters(10) drops two
+    // bits while Characters(5) to Bits(13) invents a latitude bit.
+    pub fn with_precision(&self, precision: Precision) -> GeohashBits {
+        match self.try_with_precision(precision) {
+            Ok(bits) => bits,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    pub fn try_with_precision(&self, precision: Precision) -> Result<GeohashBits, GeohashError> {
+        precision.validate()?;
+        if precision.significant_bits() <= self.precision.significant_bits() {
+            Ok(self.truncated(precision))
+        } else {
+            // the first descendant is the one padded with zeros
+            Ok(self.descendants(precision).next().unwrap())
+        }
+    }
+
+    // The finest whole character cell containing this one, exact unlike
+    // Precision::character_precision, which just floors. None for Bits(1)
+    // and Bits(2), which are coarser than a single character.
+    pub fn to_character_precision(&self) -> Option<GeohashBits> {
+        self.ancestor(Precision::Characters(self.precision.significant_bits() / 5))
+    }
+
+    // strictly coarser and containing other
+    pub fn is_ancestor_of(&self, other: &GeohashBits) -> bool {
+        self.precision.significant_bits() < other.precision.significant_bits()
+            && other.truncated(self.precision).bits == self.bits
+    }
+
+    // the finest cell containing both, in characters if both are characters
+    // and in bits otherwise, None when they already differ in the first bits
+    pub fn common_ancestor(a: &GeohashBits, b: &GeohashBits) -> Option<GeohashBits> {
+        let (a_key, a_bits) = a.z_order_key();
+        let (b_key, b_bits) = b.z_order_key();
+        let common_bits = ((a_key ^ b_key).leading_zeros() as u8)
+            .min(a_bits)
+            .min(b_bits);
+        let precision = match (a.precision, b.precision) {
+            (Precision::Characters(_), Precision::Characters(_)) => {
+                Precision::Characters(common_bits / 5)
+            }
+            _ => Precision::Bits(common_bits / 2),
+        };
+        a.ancestor(precision)
+    }
+
+    fn truncated(&self, precision: Precision) -> GeohashBits {
+        let dropped_bits = self.precision.significant_bits() - precision.significant_bits();
+        let prefix = self.bits >> self.precision.padding_bits() >> dropped_bits;
+        GeohashBits {
+            bits: prefix << precision.padding_bits(),
+            precision,
+        }
+    }
+
+    // significant bits aligned to the top of the word, so cells sort in
+    // z-order with ancestors ahead of their descendants
+    pub(crate) fn z_order_key(&self) -> (u64, u8) {
+        let significant_bits = self.precision.significant_bits();
+        let prefix = self.bits >> self.precision.padding_bits();
+        let aligned = if significant_bits == 0 {
+            0
+        } else {
+            prefix << (64 - significant_bits)
+        };
+        (aligned, significant_bits)
+    }
+
     pub fn bounding_box(&self) -> BoundingBox {
         let (mut lat_bits, lon_bits) = ops::deinterleave_bits(self.bits);
         let mut lat_precision = self.precision;
@@ -253,16 +547,88 @@ impl GeohashBits {
         }
     }
 
-    pub fn neighbor(&self, neighbor: &Neighbor) -> GeohashBits {
+    // Longitude wraps around the 180th meridian, latitude stops at the poles
+    // so there is no North neighbor for a cell touching +90 and no South
+    // neighbor for a cell touching -90.
+    #[inline]
+    pub fn neighbor(&self, neighbor: &Neighbor) -> Option<GeohashBits> {
         match neighbor {
-            Neighbor::North => self.incremented(InterleaveSet::Evens, 1),
-            Neighbor::South => self.incremented(InterleaveSet::Evens, -1),
-            Neighbor::East => self.incremented(InterleaveSet::Odds, 1),
-            Neighbor::West => self.incremented(InterleaveSet::Odds, -1),
+            Neighbor::North => self.latitude_neighbor(1),
+            Neighbor::South => self.latitude_neighbor(-1),
+            Neighbor::East => Some(self.incremented(InterleaveSet::Odds, 1)),
+            Neighbor::West => Some(self.incremented(InterleaveSet::Odds, -1)),
+            Neighbor::NorthWest => self.diagonal(1, -1),
+            Neighbor::NorthEast => self.diagonal(1, 1),
+            Neighbor::SouthWest => self.diagonal(-1, -1),
+            Neighbor::SouthEast => self.diagonal(-1, 1),
+        }
+    }
+
+    pub fn neighbors(&self) -> Neighbors {
+        let north = self.latitude_neighbor(1);
+        let south = self.latitude_neighbor(-1);
+        Neighbors {
+            center: *self,
+            north,
+            north_east: north.map(|bits| bits.incremented(InterleaveSet::Odds, 1)),
+            east: self.incremented(InterleaveSet::Odds, 1),
+            south_east: south.map(|bits| bits.incremented(InterleaveSet::Odds, 1)),
+            south,
+            south_west: south.map(|bits| bits.incremented(InterleaveSet::Odds, -1)),
+            west: self.incremented(InterleaveSet::Odds, -1),
+            north_west: north.map(|bits| bits.incremented(InterleaveSet::Odds, -1)),
         }
     }
 
-    fn incremented(&self, set: InterleaveSet, direction: i32) -> GeohashBits {
+    pub fn ring(&self, k: u32) -> NeighborhoodIterator {
+        NeighborhoodIterator::new(*self, k, true)
+    }
+
+    pub fn disk(&self, k: u32) -> NeighborhoodIterator {
+        NeighborhoodIterator::new(*self, k, false)
+    }
+
+    // moves by whole cells, wrapping longitude but not latitude
+    pub(crate) fn shifted(&self, latitude_steps: i64, longitude_steps: i64) -> GeohashBits {
+        self.incremented(InterleaveSet::Evens, latitude_steps)
+            .incremented(InterleaveSet::Odds, longitude_steps)
+    }
+
+    // number of cells between this one and the pole in the given direction
+    #[inline]
+    pub(crate) fn latitude_room(&self, direction: i64) -> u64 {
+        let (mut lat_bits, _) = ops::deinterleave_bits(self.bits);
+        let mut lat_precision = self.precision.binary_precision();
+        if self.precision.is_odd_characters() {
+            lat_bits >>= 1;
+            lat_precision -= 1;
+        }
+        if direction > 0 {
+            (1u64 << lat_precision) - 1 - lat_bits as u64
+        } else {
+            lat_bits as u64
+        }
+    }
+
+    pub(crate) fn longitude_cells(&self) -> u64 {
+        1u64 << self.precision.binary_precision()
+    }
+
+    #[inline]
+    fn latitude_neighbor(&self, direction: i64) -> Option<GeohashBits> {
+        if self.latitude_room(direction) == 0 {
+            None
+        } else {
+            Some(self.incremented(InterleaveSet::Evens, direction))
+        }
+    }
+
+    fn diagonal(&self, latitude_direction: i64, longitude_direction: i64) -> Option<GeohashBits> {
+        self.latitude_neighbor(latitude_direction)
+            .map(|bits| bits.incremented(InterleaveSet::Odds, longitude_direction))
+    }
+
+    fn incremented(&self, set: InterleaveSet, direction: i64) -> GeohashBits {
         if direction == 0 {
             return GeohashBits {
                 bits: self.bits,
@@ -274,16 +640,18 @@ impl GeohashBits {
         let binary_precision = self.precision.binary_precision() as u64;
         let increment = set.keep_mask() >> (64 - 2 * binary_precision);
         let shift_bits = InterleaveSet::Evens == set && self.precision.is_odd_characters();
+        // truncating the step count is fine since the axis wraps at a power of two anyway
+        let step = set.spread(direction.unsigned_abs() as u32);
 
         if shift_bits {
             modify_bits >>= 2;
         }
 
+        // wrapping so carries off either end fall outside the mask below
         if direction > 0 {
-            modify_bits += increment + 1;
+            modify_bits = (modify_bits | increment).wrapping_add(step);
         } else {
-            modify_bits |= increment;
-            modify_bits -= increment + 1;
+            modify_bits = modify_bits.wrapping_sub(step);
         }
 
         if shift_bits {
@@ -299,12 +667,80 @@ impl GeohashBits {
     }
 }
 
+// finer precisions sort after coarser ones, bits before characters of the same size
+impl Ord for Precision {
+    fn cmp(&self, other: &Precision) -> Ordering {
+        self.significant_bits()
+            .cmp(&other.significant_bits())
+            .then_with(|| match (self, other) {
+                (Precision::Bits(_), Precision::Characters(_)) => Ordering::Less,
+                (Precision::Characters(_), Precision::Bits(_)) => Ordering::Greater,
+                _ => Ordering::Equal,
+            })
+            // significant_bits saturates out of range, keep those distinct
+            .then_with(|| match (self, other) {
+                (Precision::Bits(a), Precision::Bits(b))
+                | (Precision::Characters(a), Precision::Characters(b)) => a.cmp(b),
+                _ => Ordering::Equal,
+            })
+    }
+}
+
+impl PartialOrd for Precision {
+    fn partial_cmp(&self, other: &Precision) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Precision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Precision::Bits(n) => write!(f, "{} bits", n),
+            Precision::Characters(n) => write!(f, "{} characters", n),
+        }
+    }
+}
+
+// z-order across precisions, ancestors sort before their descendants
+impl Ord for GeohashBits {
+    fn cmp(&self, other: &GeohashBits) -> Ordering {
+        self.z_order_key()
+            .cmp(&other.z_order_key())
+            .then_with(|| self.precision.cmp(&other.precision))
+    }
+}
+
+impl PartialOrd for GeohashBits {
+    fn partial_cmp(&self, other: &GeohashBits) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// the base32 hash, truncated to whole characters for bit precisions
+impl fmt::Display for GeohashBits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_hash(f)
+    }
+}
+
+impl FromStr for GeohashBits {
+    type Err = GeohashError;
+
+    fn from_str(hash: &str) -> Result<GeohashBits, GeohashError> {
+        GeohashBits::try_from_hash(hash)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::GeohashBits;
+    use crate::GeohashError;
     use crate::Location;
+    use crate::Neighbor;
     use crate::Precision;
     use assert_approx_eq::assert_approx_eq;
+    use std::cmp::Ordering;
+    use std::collections::HashSet;
 
     #[test]
     fn test_even_string_encoding() {
@@ -432,4 +868,704 @@ mod tests {
             1.0e-13
         );
     }
+
+    #[test]
+    fn test_try_from_location() {
+        let location = Location {
+            longitude: -0.1,
+            latitude: 51.5,
+        };
+        assert_eq!(
+            GeohashBits::try_from_location(&location, Precision::Characters(12))
+                .unwrap()
+                .hash(),
+            "gcpuvxr1jzfd"
+        );
+        assert_eq!(
+            GeohashBits::try_from_location(&location, Precision::Characters(13)).err(),
+            Some(GeohashError::PrecisionOutOfRange(33))
+        );
+        assert_eq!(
+            GeohashBits::try_from_location(&location, Precision::Bits(0)).err(),
+            Some(GeohashError::PrecisionOutOfRange(0))
+        );
+        assert_eq!(
+            GeohashBits::try_from_location(
+                &Location {
+                    longitude: -200.0,
+                    latitude: 51.5,
+                },
+                Precision::Bits(20)
+            )
+            .err(),
+            Some(GeohashError::LongitudeOutOfRange(-200.0))
+        );
+    }
+
+    #[test]
+    fn test_try_from_hash() {
+        assert_eq!(
+            GeohashBits::try_from_hash("u10hfr2c4pv6").unwrap().bits(),
+            0xd041075c4b25766
+        );
+        assert_eq!(
+            GeohashBits::try_from_hash("u10hfa").err(),
+            Some(GeohashError::InvalidCharacter {
+                character: 'a',
+                position: 5
+            })
+        );
+        assert_eq!(
+            GeohashBits::try_from_hash("U10HFR2C4PV6"),
+            GeohashBits::try_from_hash("u10hfr2c4pv6")
+        );
+        assert_eq!(
+            GeohashBits::try_from_hash("u1éa").err(),
+            Some(GeohashError::InvalidCharacter {
+                character: 'é',
+                position: 2
+            })
+        );
+        assert_eq!(
+            GeohashBits::try_from_hash("ua1é").err(),
+            Some(GeohashError::InvalidCharacter {
+                character: 'a',
+                position: 1
+            })
+        );
+        assert_eq!(
+            GeohashBits::try_from_hash("u10hfr2c4pv6b").err(),
+            Some(GeohashError::HashTooLong(13))
+        );
+        // lengths count characters, not bytes
+        assert_eq!(
+            GeohashBits::try_from_hash("éééééééééééé").err(),
+            Some(GeohashError::InvalidCharacter {
+                character: 'é',
+                position: 0
+            })
+        );
+        assert_eq!(
+            GeohashBits::try_from_hash("ééééééééééééé").err(),
+            Some(GeohashError::HashTooLong(13))
+        );
+        assert_eq!(
+            GeohashBits::try_from_hash("").err(),
+            Some(GeohashError::PrecisionOutOfRange(0))
+        );
+    }
+
+    #[test]
+    fn test_try_from_hash_bytes() {
+        assert_eq!(
+            GeohashBits::from_hash_bytes(b"gcpuv"),
+            GeohashBits::from_hash("gcpuv")
+        );
+        assert_eq!(
+            GeohashBits::from_hash_bytes(b"GcPuV"),
+            GeohashBits::from_hash("gcpuv")
+        );
+        assert_eq!(
+            GeohashBits::try_from_hash_bytes(b"gcp\xffv").err(),
+            Some(GeohashError::InvalidCharacter {
+                character: '\u{ff}',
+                position: 3
+            })
+        );
+        assert_eq!(
+            GeohashBits::try_from_hash_bytes(b"gcpui").err(),
+            Some(GeohashError::InvalidCharacter {
+                character: 'i',
+                position: 4
+            })
+        );
+        for &c in b"ailoAILO" {
+            assert!(GeohashBits::try_from_hash_bytes(&[c]).is_err());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_hash() {
+        let _ = GeohashBits::from_hash("u10hfi");
+    }
+
+    #[test]
+    fn test_cardinal_neighbors() {
+        let bits = GeohashBits::from_hash("u10hfr2c");
+        assert_eq!(bits.neighbor(&Neighbor::North).unwrap().hash(), "u10hfr2f");
+        assert_eq!(bits.neighbor(&Neighbor::East).unwrap().hash(), "u10hfr31");
+        assert_eq!(bits.neighbor(&Neighbor::South).unwrap().hash(), "u10hfr2b");
+        assert_eq!(bits.neighbor(&Neighbor::West).unwrap().hash(), "u10hfr29");
+    }
+
+    #[test]
+    fn test_diagonal_neighbors() {
+        let bits = GeohashBits::from_hash("u10hfr2c");
+        assert_eq!(
+            bits.neighbor(&Neighbor::NorthEast).unwrap().hash(),
+            "u10hfr34"
+        );
+        assert_eq!(
+            bits.neighbor(&Neighbor::NorthWest).unwrap().hash(),
+            "u10hfr2d"
+        );
+        assert_eq!(
+            bits.neighbor(&Neighbor::SouthEast).unwrap().hash(),
+            "u10hfr30"
+        );
+        assert_eq!(
+            bits.neighbor(&Neighbor::SouthWest).unwrap().hash(),
+            "u10hfr28"
+        );
+    }
+
+    #[test]
+    fn test_odd_character_diagonal_neighbors() {
+        let bits = GeohashBits::from_hash("gcpuv");
+        assert_eq!(bits.neighbor(&Neighbor::NorthEast).unwrap().hash(), "gcpvn");
+        assert_eq!(bits.neighbor(&Neighbor::SouthWest).unwrap().hash(), "gcpus");
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let hashes: Vec<String> = GeohashBits::from_hash("u10hfr2c")
+            .neighbors()
+            .to_array()
+            .iter()
+            .map(|bits| bits.unwrap().hash())
+            .collect();
+        assert_eq!(
+            hashes,
+            vec![
+                "u10hfr2d", "u10hfr2f", "u10hfr34", "u10hfr29", "u10hfr2c", "u10hfr31", "u10hfr28",
+                "u10hfr2b", "u10hfr30",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_north_pole_neighbors() {
+        let bits = GeohashBits::from_hash("zzzz");
+        assert!(bits.neighbor(&Neighbor::North).is_none());
+        assert!(bits.neighbor(&Neighbor::NorthEast).is_none());
+        assert!(bits.neighbor(&Neighbor::NorthWest).is_none());
+        assert_eq!(bits.neighbor(&Neighbor::South).unwrap().hash(), "zzzy");
+        assert_eq!(bits.neighbor(&Neighbor::SouthEast).unwrap().hash(), "bpbn");
+
+        let neighbors = bits.neighbors();
+        assert!(neighbors.north.is_none());
+        assert_eq!(
+            neighbors.to_array().iter().filter(|n| n.is_some()).count(),
+            6
+        );
+
+        let odd = GeohashBits::from_hash("zzzzz");
+        assert!(odd.neighbor(&Neighbor::North).is_none());
+        assert_eq!(odd.neighbor(&Neighbor::South).unwrap().hash(), "zzzzx");
+    }
+
+    #[test]
+    fn test_south_pole_neighbors() {
+        let bits = GeohashBits::from_hash("0000");
+        assert!(bits.neighbor(&Neighbor::South).is_none());
+        assert!(bits.neighbor(&Neighbor::SouthEast).is_none());
+        assert!(bits.neighbor(&Neighbor::SouthWest).is_none());
+        assert_eq!(bits.neighbor(&Neighbor::North).unwrap().hash(), "0001");
+        assert_eq!(bits.neighbor(&Neighbor::NorthWest).unwrap().hash(), "pbpc");
+
+        let odd = GeohashBits::from_hash("00000");
+        assert!(odd.neighbor(&Neighbor::South).is_none());
+        assert_eq!(odd.neighbor(&Neighbor::North).unwrap().hash(), "00002");
+
+        let binary = GeohashBits::from_location(
+            &Location {
+                longitude: 0.0,
+                latitude: -90.0,
+            },
+            Precision::Bits(32),
+        );
+        assert!(binary.neighbor(&Neighbor::South).is_none());
+    }
+
+    #[test]
+    fn test_antimeridian_neighbors() {
+        let east_edge = GeohashBits::from_location(
+            &Location {
+                longitude: 179.99,
+                latitude: 0.0,
+            },
+            Precision::Characters(4),
+        );
+        let east = east_edge.neighbor(&Neighbor::East).unwrap();
+        let bounds = east.bounding_box();
+        assert_approx_eq!(bounds.min().longitude, -180.0, 1e-12);
+        assert_eq!(
+            east.neighbor(&Neighbor::West).unwrap().hash(),
+            east_edge.hash()
+        );
+
+        assert_eq!(
+            GeohashBits::from_hash("zzzz")
+                .neighbor(&Neighbor::East)
+                .unwrap()
+                .hash(),
+            "bpbp"
+        );
+        assert_eq!(
+            GeohashBits::from_hash("0000")
+                .neighbor(&Neighbor::West)
+                .unwrap()
+                .hash(),
+            "pbpb"
+        );
+
+        let binary = GeohashBits::from_location(
+            &Location {
+                longitude: 180.0,
+                latitude: 0.0,
+            },
+            Precision::Bits(32),
+        );
+        let wrapped = binary.neighbor(&Neighbor::East).unwrap();
+        let bounds = wrapped.bounding_box();
+        assert_approx_eq!(bounds.min().longitude, -180.0, 1e-12);
+    }
+
+    #[test]
+    fn test_parent() {
+        let bits = GeohashBits::from_hash("u10hfr2c");
+        assert_eq!(bits.parent().unwrap().hash(), "u10hfr2");
+        assert_eq!(bits.parent().unwrap().parent().unwrap().hash(), "u10hfr");
+        assert!(GeohashBits::from_hash("u").parent().is_none());
+
+        let bits = GeohashBits::from_location(
+            &Location {
+                longitude: -0.1,
+                latitude: 51.5,
+            },
+            Precision::Bits(26),
+        );
+        let parent = bits.parent().unwrap();
+        assert_eq!(parent.precision().binary_precision(), 25);
+        assert_eq!(parent.bits(), bits.bits() >> 2);
+    }
+
+    #[test]
+    fn test_odd_character_parent() {
+        let bits = GeohashBits::from_location(
+            &Location {
+                longitude: -0.1,
+                latitude: 51.5,
+            },
+            Precision::Characters(5),
+        );
+        assert_eq!(bits.hash(), "gcpuv");
+        assert_eq!(bits.parent().unwrap().hash(), "gcpu");
+        assert_eq!(bits.parent().unwrap().parent().unwrap().hash(), "gcp");
+        assert_eq!(bits.bits(), GeohashBits::from_hash("gcpuv").bits());
+    }
+
+    #[test]
+    fn test_ancestor() {
+        let bits = GeohashBits::from_hash("u10hfr2c");
+        assert_eq!(
+            bits.ancestor(Precision::Characters(3)).unwrap().hash(),
+            "u10"
+        );
+        let binary = bits.ancestor(Precision::Bits(10)).unwrap();
+        assert_eq!(binary.hash(), "u10h");
+        assert_eq!(binary.precision().binary_precision(), 10);
+        assert_eq!(
+            bits.ancestor(Precision::Characters(8)).unwrap().hash(),
+            "u10hfr2c"
+        );
+        assert!(bits.ancestor(Precision::Characters(9)).is_none());
+        assert!(bits.ancestor(Precision::Bits(0)).is_none());
+    }
+
+    #[test]
+    fn test_children() {
+        let bits = GeohashBits::from_hash("gcp");
+        let children: Vec<String> = bits.children().map(|child| child.hash()).collect();
+        assert_eq!(children.len(), 32);
+        assert_eq!(children[0], "gcp0");
+        assert_eq!(children[31], "gcpz");
+
+        let bounds = bits.bounding_box();
+        for child in GeohashBits::from_hash("gcpu").children() {
+            assert_eq!(child.parent().unwrap().hash(), "gcpu");
+            let child_bounds = child.bounding_box();
+            assert!(child_bounds.min().latitude >= bounds.min().latitude);
+            assert!(child_bounds.max().longitude <= bounds.max().longitude);
+        }
+
+        let binary = bits.ancestor(Precision::Bits(7)).unwrap();
+        let children: Vec<GeohashBits> = binary.children().collect();
+        assert_eq!(children.len(), 4);
+        assert!(children.iter().all(|child| binary.is_ancestor_of(child)));
+        assert_eq!(children[0].bits(), binary.bits() << 2);
+
+        assert_eq!(GeohashBits::from_hash("u10hfr2c4pv6").children().count(), 0);
+    }
+
+    #[test]
+    fn test_is_ancestor_of() {
+        let bits = GeohashBits::from_hash("u10hfr2c");
+        assert!(GeohashBits::from_hash("u10h").is_ancestor_of(&bits));
+        assert!(GeohashBits::from_hash("u").is_ancestor_of(&bits));
+        assert!(!GeohashBits::from_hash("u10j").is_ancestor_of(&bits));
+        assert!(!bits.is_ancestor_of(&bits));
+        assert!(!bits.is_ancestor_of(&GeohashBits::from_hash("u10h")));
+        assert!(bits
+            .ancestor(Precision::Bits(9))
+            .unwrap()
+            .is_ancestor_of(&bits));
+    }
+
+    #[test]
+    fn test_common_ancestor() {
+        let a = GeohashBits::from_hash("u10hfr2c");
+        let b = GeohashBits::from_hash("u10hfr34");
+        assert_eq!(
+            GeohashBits::common_ancestor(&a, &b).unwrap().hash(),
+            "u10hfr"
+        );
+        assert_eq!(
+            GeohashBits::common_ancestor(&a, &GeohashBits::from_hash("u10"))
+                .unwrap()
+                .hash(),
+            "u10"
+        );
+        assert!(GeohashBits::common_ancestor(&a, &GeohashBits::from_hash("gcp")).is_none());
+
+        let binary =
+            GeohashBits::common_ancestor(&a, &b.ancestor(Precision::Bits(16)).unwrap()).unwrap();
+        assert_eq!(binary.precision().binary_precision(), 16);
+        assert!(binary.is_ancestor_of(&a));
+        assert!(binary.is_ancestor_of(&b));
+    }
+
+    #[test]
+    fn test_equality() {
+        let location = Location {
+            longitude: -0.1,
+            latitude: 51.5,
+        };
+        let encoded = GeohashBits::from_location(&location, Precision::Characters(5));
+        assert_eq!(encoded, GeohashBits::from_hash("gcpuv"));
+        assert_ne!(encoded, GeohashBits::from_hash("gcpuu"));
+
+        // same cell, different precision
+        let binary = GeohashBits::from_location(&location, Precision::Bits(10));
+        let characters = GeohashBits::from_location(&location, Precision::Characters(4));
+        assert_eq!(binary.bits(), characters.bits());
+        assert_ne!(binary, characters);
+
+        let set: HashSet<GeohashBits> = vec![encoded, GeohashBits::from_hash("gcpuv"), binary]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut cells: Vec<GeohashBits> = vec!["gcpv", "gcpuv", "gcpu", "gcp", "gcpuu", "b"]
+            .into_iter()
+            .map(GeohashBits::from_hash)
+            .collect();
+        let binary = GeohashBits::from_hash("gcpu")
+            .ancestor(Precision::Bits(10))
+            .unwrap();
+        cells.push(binary);
+        cells.sort();
+        let hashes: Vec<String> = cells.iter().map(|bits| bits.to_string()).collect();
+        assert_eq!(
+            hashes,
+            vec!["b", "gcp", "gcpu", "gcpu", "gcpuu", "gcpuv", "gcpv"]
+        );
+        assert_eq!(cells[2].precision(), Precision::Bits(10));
+        assert!(Precision::Bits(10) < Precision::Characters(4));
+        assert!(Precision::Characters(4) < Precision::Bits(11));
+        // out of range precisions only compare equal when they are equal
+        assert!(Precision::Bits(200) < Precision::Bits(201));
+        assert!(Precision::Characters(60) < Precision::Characters(61));
+        assert_eq!(
+            Precision::Bits(200).cmp(&Precision::Bits(200)),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_display_and_from_str() {
+        let bits: GeohashBits = "u10hfr2c".parse().unwrap();
+        assert_eq!(bits.to_string(), "u10hfr2c");
+        assert_eq!(format!("{}", Precision::Bits(26)), "26 bits");
+        assert_eq!(format!("{}", Precision::Characters(11)), "11 characters");
+        assert_eq!(
+            "u10hfi".parse::<GeohashBits>().err(),
+            Some(GeohashError::InvalidCharacter {
+                character: 'i',
+                position: 5
+            })
+        );
+        assert_eq!(
+            format!("{:?}", GeohashBits::from_hash("b")),
+            "GeohashBits { bits: 20, precision: Characters(1) }"
+        );
+    }
+
+    #[test]
+    fn test_try_from_bits() {
+        let bits = GeohashBits::from_hash("gcpuv");
+        assert_eq!(
+            GeohashBits::try_from_bits(bits.bits(), bits.precision()),
+            Ok(bits)
+        );
+        assert_eq!(
+            GeohashBits::try_from_bits(bits.bits() | 1, bits.precision()),
+            Err(GeohashError::BitsOutOfRange(bits.bits() | 1))
+        );
+        assert_eq!(
+            GeohashBits::try_from_bits(1 << 20, Precision::Bits(10)),
+            Err(GeohashError::BitsOutOfRange(1 << 20))
+        );
+        assert_eq!(
+            GeohashBits::try_from_bits(0, Precision::Bits(33)),
+            Err(GeohashError::PrecisionOutOfRange(33))
+        );
+        assert_eq!(
+            GeohashBits::try_from_bits(u64::MAX, Precision::Bits(32))
+                .unwrap()
+                .bits(),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn test_precision_out_of_range() {
+        let location = Location {
+            longitude: -0.1,
+            latitude: 51.5,
+        };
+        for (precision, binary_precision) in &[
+            (Precision::Characters(13), 33),
+            (Precision::Characters(60), 150),
+            (Precision::Characters(255), 255),
+            (Precision::Bits(33), 33),
+            (Precision::Bits(200), 200),
+        ] {
+            let error = Some(GeohashError::PrecisionOutOfRange(*binary_precision));
+            assert_eq!(
+                GeohashBits::try_from_location(&location, *precision).err(),
+                error
+            );
+            assert_eq!(GeohashBits::try_from_bits(0, *precision).err(), error);
+        }
+        let bits = GeohashBits::from_hash("gcpuvxr1jzfd");
+        assert_eq!(bits.ancestor(Precision::Characters(60)), None);
+        assert_eq!(bits.ancestor(Precision::Bits(200)), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        // the classic geohash error table
+        let bits = GeohashBits::from_hash("u10hfr2c");
+        assert_approx_eq!(bits.lat_error(), 0.000085, 1e-6);
+        assert_approx_eq!(bits.lon_error(), 0.00017, 1e-5);
+        let bits = GeohashBits::from_hash("gcpuv");
+        assert_approx_eq!(bits.lat_error(), 0.022, 1e-3);
+        assert_approx_eq!(bits.lon_error(), 0.022, 1e-3);
+        let bounds = bits.bounding_box();
+        assert_approx_eq!(
+            2.0 * bits.lat_error(),
+            bounds.max().latitude - bounds.min().latitude,
+            1e-12
+        );
+
+        let (width, height) = GeohashBits::from_hash("s0000").cell_size_meters();
+        assert_approx_eq!(width, 4_886.5, 0.1);
+        assert_approx_eq!(height, 4_886.5, 0.1);
+    }
+
+    #[test]
+    fn test_for_cell_size() {
+        assert_eq!(
+            Precision::for_cell_size(5_000.0, 5_000.0, 0.0).unwrap(),
+            Precision::Bits(13)
+        );
+        // cells are narrower away from the equator
+        assert_eq!(
+            Precision::for_cell_size(5_000.0, 5_000.0, 60.0).unwrap(),
+            Precision::Bits(12)
+        );
+        assert_eq!(
+            Precision::for_cell_size(2_500.0, 5_000.0, 60.0).unwrap(),
+            Precision::Bits(13)
+        );
+        assert_eq!(
+            Precision::for_cell_size(2_500.0, 5_000.0, 0.0).unwrap(),
+            Precision::Bits(14)
+        );
+        assert_eq!(
+            Precision::for_cell_size(1e-6, 1e-6, 0.0).unwrap(),
+            Precision::Bits(32)
+        );
+        assert_eq!(
+            Precision::for_cell_size(1e9, 1e9, 0.0).unwrap(),
+            Precision::Bits(1)
+        );
+
+        for &meters in &[1.0, 20.0, 500.0, 10_000.0] {
+            for &latitude in &[0.0, 45.0, -70.0] {
+                let precision = Precision::for_error_meters(meters, latitude).unwrap();
+                let location = Location {
+                    longitude: 12.0,
+                    latitude,
+                };
+                let bits = GeohashBits::from_location(&location, precision);
+                let east = Location {
+                    longitude: 12.0 + bits.lon_error(),
+                    latitude,
+                };
+                let north = Location {
+                    longitude: 12.0,
+                    latitude: latitude + bits.lat_error(),
+                };
+                assert!(location.distance_in_meters(&east) <= meters * 1.0001);
+                assert!(location.distance_in_meters(&north) <= meters * 1.0001);
+                let coarser = GeohashBits::from_location(&location, precision.coarser());
+                let (width, height) = coarser.cell_size_meters();
+                assert!(width.max(height) > 2.0 * meters * 0.999);
+            }
+        }
+    }
+
+    #[test]
+    fn test_for_cell_size_out_of_range() {
+        assert_eq!(
+            Precision::for_cell_size(0.0, 5_000.0, 0.0),
+            Err(GeohashError::DistanceOutOfRange(0.0))
+        );
+        assert_eq!(
+            Precision::for_error_meters(-1.0, 0.0),
+            Err(GeohashError::DistanceOutOfRange(-2.0))
+        );
+        assert!(matches!(
+            Precision::for_cell_size(5_000.0, f64::NAN, 0.0),
+            Err(GeohashError::DistanceOutOfRange(meters)) if meters.is_nan()
+        ));
+        assert_eq!(
+            Precision::for_error_meters(5_000.0, f64::NAN),
+            Err(GeohashError::NaN)
+        );
+        assert_eq!(
+            Precision::for_cell_size(5_000.0, 5_000.0, 91.0),
+            Err(GeohashError::LatitudeOutOfRange(91.0))
+        );
+    }
+
+    #[test]
+    fn test_with_precision() {
+        let location = Location {
+            longitude: -0.1,
+            latitude: 51.5,
+        };
+        let bits = GeohashBits::from_location(&location, Precision::Bits(26));
+
+        // coarsening truncates
+        let characters = bits.with_precision(Precision::Characters(10));
+        assert_eq!(characters.hash(), "gcpuvxr1jz");
+        assert_eq!(
+            characters,
+            GeohashBits::from_location(&location, Precision::Characters(10))
+        );
+        assert!(characters.is_ancestor_of(&bits));
+
+        // refining pads, so the two dropped bits do not come back
+        let padded = characters.with_precision(Precision::Bits(26));
+        assert_ne!(padded, bits);
+        assert!(characters.is_ancestor_of(&padded));
+        assert_eq!(padded.bits() & 0b11, 0);
+        assert_eq!(padded.bounding_box().min(), characters.bounding_box().min());
+
+        // odd characters to bits invents the lowest latitude bit
+        let gcpuv = GeohashBits::from_hash("gcpuv");
+        let refined = gcpuv.with_precision(Precision::Bits(13));
+        assert_eq!(refined.precision(), Precision::Bits(13));
+        assert_eq!(refined.bits(), gcpuv.bits());
+        assert_eq!(refined.bounding_box().min(), gcpuv.bounding_box().min());
+        let refined_bounds = refined.bounding_box();
+        assert_approx_eq!(
+            refined_bounds.max().latitude,
+            gcpuv.bounding_box().center().latitude,
+            1e-12
+        );
+        assert_eq!(
+            gcpuv.with_precision(Precision::Bits(12)),
+            gcpuv.ancestor(Precision::Bits(12)).unwrap()
+        );
+
+        // the same number of significant bits just changes the kind
+        assert_eq!(
+            GeohashBits::from_hash("gcpuvxr1jz")
+                .with_precision(Precision::Bits(25))
+                .bits(),
+            GeohashBits::from_hash("gcpuvxr1jz").bits()
+        );
+        assert_eq!(gcpuv.with_precision(gcpuv.precision()), gcpuv);
+    }
+
+    #[test]
+    fn test_try_with_precision() {
+        let gcpuv = GeohashBits::from_hash("gcpuv");
+        assert_eq!(
+            gcpuv.try_with_precision(Precision::Characters(3)),
+            Ok(GeohashBits::from_hash("gcp"))
+        );
+        assert_eq!(
+            gcpuv.try_with_precision(Precision::Characters(13)),
+            Err(GeohashError::PrecisionOutOfRange(33))
+        );
+        assert_eq!(
+            gcpuv.try_with_precision(Precision::Bits(0)),
+            Err(GeohashError::PrecisionOutOfRange(0))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_with_precision_out_of_range() {
+        GeohashBits::from_hash("gcpuv").with_precision(Precision::Characters(13));
+    }
+
+    #[test]
+    fn test_to_character_precision() {
+        let location = Location {
+            longitude: -0.1,
+            latitude: 51.5,
+        };
+        let bits = GeohashBits::from_location(&location, Precision::Bits(26));
+        // 52 bits hold 10 whole characters
+        assert_eq!(bits.to_character_precision().unwrap().hash(), "gcpuvxr1jz");
+        // 14 bits hold 2 whole characters, character_precision agrees here
+        let bits = GeohashBits::from_location(&location, Precision::Bits(7));
+        assert_eq!(bits.precision().character_precision(), 2);
+        assert_eq!(bits.to_character_precision().unwrap().hash(), "gc");
+        assert!(bits.to_character_precision().unwrap().is_ancestor_of(&bits));
+
+        let gcpuv = GeohashBits::from_hash("gcpuv");
+        assert_eq!(gcpuv.to_character_precision(), Some(gcpuv));
+        assert_eq!(
+            GeohashBits::from_location(&location, Precision::Bits(3))
+                .to_character_precision()
+                .unwrap()
+                .hash(),
+            "g"
+        );
+        assert_eq!(
+            GeohashBits::from_location(&location, Precision::Bits(2)).to_character_precision(),
+            None
+        );
+    }
 }
@@ -4,31 +4,76 @@ pub struct GeohashIterator {
     bounds: BoundingBox,
     lat_baseline: GeohashBits,
     current: Option<GeohashBits>,
+    column: u64,
 }
 
 impl GeohashIterator {
     pub fn new(bounds: BoundingBox, bit_precision: u8) -> GeohashIterator {
-        let lat_baseline =
-            GeohashBits::from_location(&bounds.min(), Precision::Bits(bit_precision));
+        GeohashIterator::with_precision(bounds, Precision::Bits(bit_precision))
+    }
+
+    pub fn with_precision(bounds: BoundingBox, precision: Precision) -> GeohashIterator {
+        let lat_baseline = GeohashBits::from_location(bounds.min(), precision);
         GeohashIterator {
             bounds,
             lat_baseline,
             current: Some(lat_baseline),
+            column: 0,
         }
     }
 
+    // the western cell of every row, from the south
+    #[cfg(feature = "rayon")]
+    pub(crate) fn row_starts(bounds: BoundingBox, precision: Precision) -> Vec<GeohashBits> {
+        let first = GeohashBits::from_location(bounds.min(), precision);
+        std::iter::successors(Some(first), |bits| {
+            bits.neighbor(&Neighbor::North)
+                .filter(|bits| bounds.intersects(&bits.bounding_box()))
+        })
+        .collect()
+    }
+
+    // the cells of the row starting at start, as advance_current visits them
+    #[cfg(feature = "rayon")]
+    pub(crate) fn row(
+        bounds: BoundingBox,
+        start: GeohashBits,
+    ) -> impl Iterator<Item = GeohashBits> {
+        std::iter::successors(Some(start), move |bits| {
+            bits.neighbor(&Neighbor::East)
+                .filter(|bits| bounds.intersects(&bits.bounding_box()))
+        })
+        .take(start.longitude_cells() as usize)
+    }
+
+    // classify each cell as entirely inside the bounds or not
+    pub fn with_coverage(self) -> CoverageIterator {
+        CoverageIterator::from_cells(self)
+    }
+
+    pub(crate) fn bounds(&self) -> &BoundingBox {
+        &self.bounds
+    }
+
     fn advance_current(&mut self) {
-        // advance eastward until we are out of the bounds then advance northward
+        // advance eastward until we are out of the bounds, or have gone all the
+        // way round, then advance northward
         if let Some(bits) = self.current {
-            let bits = bits.neighbor(&Neighbor::East);
-            if self.bounds.intersects(&bits.bounding_box()) {
-                self.current = Some(bits);
+            let east = bits
+                .neighbor(&Neighbor::East)
+                .filter(|_| self.column + 1 < bits.longitude_cells())
+                .filter(|bits| self.bounds.intersects(&bits.bounding_box()));
+            if east.is_some() {
+                self.current = east;
+                self.column += 1;
             } else {
-                self.lat_baseline = self.lat_baseline.neighbor(&Neighbor::North);
-                if self.bounds.intersects(&self.lat_baseline.bounding_box()) {
-                    self.current = Some(self.lat_baseline);
-                } else {
-                    self.current = Option::None;
+                self.column = 0;
+                self.current = self
+                    .lat_baseline
+                    .neighbor(&Neighbor::North)
+                    .filter(|bits| self.bounds.intersects(&bits.bounding_box()));
+                if let Some(bits) = self.current {
+                    self.lat_baseline = bits;
                 }
             }
         }
@@ -39,7 +84,7 @@ impl std::iter::Iterator for GeohashIterator {
     type Item = GeohashBits;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let value = self.current.clone();
+        let value = self.current;
         self.advance_current();
         value
     }
@@ -48,8 +93,11 @@ impl std::iter::Iterator for GeohashIterator {
 #[cfg(test)]
 mod tests {
     use crate::BoundingBox;
+    use crate::GeohashBits;
     use crate::GeohashIterator;
     use crate::Location;
+    use crate::Precision;
+    use std::collections::HashSet;
 
     #[test]
     fn test_iterator() {
@@ -71,4 +119,63 @@ mod tests {
         assert_eq!(iterator.next().unwrap().hash(), "u10hfr34");
         assert!(iterator.next().is_none());
     }
+
+    #[test]
+    fn test_antimeridian() {
+        let bounds = BoundingBox::new(
+            &Location {
+                longitude: 170.0,
+                latitude: -10.0,
+            },
+            &Location {
+                longitude: -170.0,
+                latitude: 10.0,
+            },
+        );
+        let cells: Vec<GeohashBits> = GeohashIterator::new(bounds, 5).collect();
+        // 11.25 degree columns either side of 180, 5.625 degree rows
+        assert_eq!(cells.len(), 2 * 4);
+        assert!(cells
+            .iter()
+            .all(|bits| bounds.intersects(&bits.bounding_box())));
+    }
+
+    #[test]
+    fn test_whole_world() {
+        let bounds = BoundingBox::new(
+            &Location {
+                longitude: -180.0,
+                latitude: -90.0,
+            },
+            &Location {
+                longitude: 180.0,
+                latitude: 90.0,
+            },
+        );
+        assert_eq!(GeohashIterator::new(bounds, 3).count(), 64);
+    }
+
+    #[test]
+    fn test_odd_characters() {
+        let bounds = GeohashBits::from_hash("gcpu").bounding_box();
+        let hashes: Vec<String> = GeohashIterator::with_precision(bounds, Precision::Characters(5))
+            .map(|bits| bits.hash())
+            .collect();
+        // 5 characters split 13 longitude bits and 12 latitude bits, 8 by 4
+        assert_eq!(hashes.len(), 32 + 8 + 4 + 1);
+        assert!(hashes.iter().all(|hash| hash.len() == 5));
+        let inside: HashSet<&String> = hashes
+            .iter()
+            .filter(|hash| hash.starts_with("gcpu"))
+            .collect();
+        assert_eq!(inside.len(), 32);
+        assert_eq!(hashes[0], "gcpu0");
+    }
+
+    #[test]
+    fn test_even_characters() {
+        let bounds = GeohashBits::from_hash("gcpu").bounding_box();
+        let iterator = GeohashIterator::with_precision(bounds, Precision::Characters(6));
+        assert!(iterator.map(|bits| bits.hash()).all(|hash| hash.len() == 6));
+    }
 }
@@ -0,0 +1,108 @@
+use crate::geohash_bits::MAX_CHARACTER_PRECISION;
+use std::fmt;
+use std::ops::Deref;
+
+// A base32 hash stored inline, so producing one does not allocate.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GeohashStr {
+    bytes: [u8; MAX_CHARACTER_PRECISION],
+    len: u8,
+}
+
+impl GeohashStr {
+    pub(crate) fn new() -> GeohashStr {
+        GeohashStr {
+            bytes: [0; MAX_CHARACTER_PRECISION],
+            len: 0,
+        }
+    }
+
+    // callers only push base32 characters, which are ascii
+    pub(crate) fn push(&mut self, character: u8) {
+        debug_assert!(character.is_ascii());
+        self.bytes[self.len as usize] = character;
+        self.len += 1;
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len as usize]).expect("base32 is ascii")
+    }
+}
+
+impl Deref for GeohashStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for GeohashStr {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq<str> for GeohashStr {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for GeohashStr {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl fmt::Debug for GeohashStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for GeohashStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{GeohashBits, GeohashStr, Location, Precision};
+    use std::fmt::Write;
+
+    #[test]
+    fn test_hash_inline() {
+        let bits = GeohashBits::from_hash("u10hfr2c4pv6");
+        let hash: GeohashStr = bits.hash_inline();
+        assert_eq!(hash, "u10hfr2c4pv6");
+        assert_eq!(hash.len(), 12);
+        assert_eq!(hash.to_uppercase(), "U10HFR2C4PV6");
+        assert_eq!(format!("{:?}", hash), "\"u10hfr2c4pv6\"");
+
+        let copy = hash;
+        assert_eq!(copy, hash);
+        assert_eq!(&*GeohashBits::from_hash("gcpuv").hash_inline(), "gcpuv");
+
+        let bits = GeohashBits::from_location(
+            &Location {
+                longitude: 0.0,
+                latitude: 0.0,
+            },
+            Precision::Bits(1),
+        );
+        assert!(bits.hash_inline().is_empty());
+    }
+
+    #[test]
+    fn test_write_hash() {
+        let mut out = String::new();
+        for hash in &["gcpuv", "u10h"] {
+            GeohashBits::from_hash(hash).write_hash(&mut out).unwrap();
+            out.write_char(',').unwrap();
+        }
+        assert_eq!(out, "gcpuv,u10h,");
+        assert_eq!(GeohashBits::from_hash("gcpuv").to_string(), "gcpuv");
+    }
+}
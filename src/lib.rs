@@ -1,5 +1,10 @@
 use std::f64;
 
+mod ops;
+
+pub mod error;
+pub use self::error::GeohashError;
+
 pub mod location;
 pub use self::location::Location;
 
@@ -7,18 +12,54 @@ pub mod bounding_box;
 pub use self::bounding_box::BoundingBox;
 
 pub mod geohash_bits;
-pub use self::geohash_bits::{GeohashBits, Precision};
+pub use self::geohash_bits::{GeohashBits, Neighbors, Precision};
+
+pub mod geohash_str;
+pub use self::geohash_str::GeohashStr;
 
 pub mod geohash_iterator;
 pub use self::geohash_iterator::GeohashIterator;
 
+pub mod coverage_iterator;
+pub use self::coverage_iterator::CoverageIterator;
+
+pub mod neighborhood_iterator;
+pub use self::neighborhood_iterator::NeighborhoodIterator;
+
+pub mod polygon;
+pub use self::polygon::Polygon;
+
+pub mod cover;
+pub use self::cover::{cover_circle, cover_polygon, cover_region, CoverBuilder, Coverage, Region};
+
+pub mod redis;
+
+pub mod batch;
+pub use self::batch::{decode_batch, encode_batch, encode_batch_soa, encode_batch_strings};
+
+#[cfg(feature = "serde")]
+pub mod serialization;
+
+#[cfg(feature = "geo-types")]
+mod geo;
+
+#[cfg(feature = "rayon")]
+pub mod parallel;
+#[cfg(feature = "rayon")]
+pub use self::parallel::{par_cover_region, par_encode_batch, par_geohash_iter};
+
 type LocationRange = std::ops::RangeInclusive<f64>;
-const LONGITUDE_RANGE: LocationRange = (-180.0..=180.0);
-const LATITUDE_RANGE: LocationRange = (-90.0..=90.0);
+const LONGITUDE_RANGE: LocationRange = -180.0..=180.0;
+const LATITUDE_RANGE: LocationRange = -90.0..=90.0;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Neighbor {
     West,
     East,
     South,
     North,
+    NorthWest,
+    NorthEast,
+    SouthWest,
+    SouthEast,
 }
@@ -1,22 +1,47 @@
-#[derive(Clone, Copy)]
+use crate::GeohashError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "crate::serialization::RawLocation")
+)]
 pub struct Location {
     pub longitude: f64,
     pub latitude: f64,
 }
 
-const RADIANS_PER_DEGREE: f64 = std::f64::consts::PI / 180.0;
-const EARTH_DISTANCE_METERS: f64 = 2.0 * 6_371_000.0;
+pub(crate) const RADIANS_PER_DEGREE: f64 = std::f64::consts::PI / 180.0;
+pub(crate) const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+const EARTH_DISTANCE_METERS: f64 = 2.0 * EARTH_RADIUS_METERS;
 
 impl Location {
+    pub fn try_new(longitude: f64, latitude: f64) -> Result<Location, GeohashError> {
+        let location = Location {
+            longitude,
+            latitude,
+        };
+        location.validate()?;
+        Ok(location)
+    }
+
+    pub fn validate(&self) -> Result<(), GeohashError> {
+        if self.longitude.is_nan() || self.latitude.is_nan() {
+            return Err(GeohashError::NaN);
+        }
+        if !crate::LONGITUDE_RANGE.contains(&self.longitude) {
+            return Err(GeohashError::LongitudeOutOfRange(self.longitude));
+        }
+        if !crate::LATITUDE_RANGE.contains(&self.latitude) {
+            return Err(GeohashError::LatitudeOutOfRange(self.latitude));
+        }
+        Ok(())
+    }
+
     pub fn validate_range(&self) {
-        assert!(
-            crate::LONGITUDE_RANGE.contains(&self.longitude),
-            "longitude out of range"
-        );
-        assert!(
-            crate::LATITUDE_RANGE.contains(&self.latitude),
-            "latitude out of range"
-        );
+        if let Err(error) = self.validate() {
+            panic!("{}", error);
+        }
     }
 
     pub fn distance_in_meters(&self, to: &Location) -> f64 {
@@ -37,6 +62,7 @@ impl Location {
 
 #[cfg(test)]
 mod tests {
+    use crate::GeohashError;
     use crate::Location;
     use assert_approx_eq::assert_approx_eq;
 
@@ -63,4 +89,24 @@ mod tests {
         }
         .validate_range();
     }
+
+    #[test]
+    fn test_try_new() {
+        let location = Location::try_new(-0.1, 51.5).unwrap();
+        assert_approx_eq!(location.longitude, -0.1, 1e-12);
+        assert_approx_eq!(location.latitude, 51.5, 1e-12);
+
+        assert_eq!(
+            Location::try_new(181.0, 0.0).err(),
+            Some(GeohashError::LongitudeOutOfRange(181.0))
+        );
+        assert_eq!(
+            Location::try_new(0.0, -90.5).err(),
+            Some(GeohashError::LatitudeOutOfRange(-90.5))
+        );
+        assert_eq!(
+            Location::try_new(f64::NAN, 0.0).err(),
+            Some(GeohashError::NaN)
+        );
+    }
 }
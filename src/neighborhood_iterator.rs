@@ -0,0 +1,212 @@
+use crate::*;
+
+// Cells within k steps of a center cell, row by row from the south west.
+// Rows beyond a pole are skipped and columns wrap around the 180th meridian,
+// never producing the same cell twice.
+pub struct NeighborhoodIterator {
+    k: i64,
+    hollow: bool,
+    longitude_cells: i64,
+    row: Option<GeohashBits>,
+    row_offset: i64,
+    max_row_offset: i64,
+    column: i64,
+}
+
+impl NeighborhoodIterator {
+    pub(crate) fn new(center: GeohashBits, k: u32, hollow: bool) -> NeighborhoodIterator {
+        let k = k as i64;
+        let south = k.min(center.latitude_room(-1) as i64);
+        let north = k.min(center.latitude_room(1) as i64);
+        NeighborhoodIterator {
+            k,
+            hollow,
+            longitude_cells: center.longitude_cells() as i64,
+            row: Some(center.shifted(-south, 0)),
+            row_offset: -south,
+            max_row_offset: north,
+            column: 0,
+        }
+    }
+
+    fn row_length(&self) -> i64 {
+        if !self.hollow || self.row_offset.abs() == self.k {
+            (2 * self.k + 1).min(self.longitude_cells)
+        } else if 2 * self.k < self.longitude_cells {
+            2
+        } else if 2 * self.k == self.longitude_cells {
+            // east and west edges are the same column
+            1
+        } else {
+            // the edges wrapped around closer than k
+            0
+        }
+    }
+
+    fn column_offset(&self) -> i64 {
+        if !self.hollow || self.row_offset.abs() == self.k {
+            self.column - self.k
+        } else if self.column == 0 {
+            -self.k
+        } else {
+            self.k
+        }
+    }
+}
+
+impl std::iter::Iterator for NeighborhoodIterator {
+    type Item = GeohashBits;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(row) = self.row {
+            if self.column < self.row_length() {
+                let bits = row.shifted(0, self.column_offset());
+                self.column += 1;
+                return Some(bits);
+            }
+            self.column = 0;
+            self.row_offset += 1;
+            self.row = if self.row_offset <= self.max_row_offset {
+                Some(row.shifted(1, 0))
+            } else {
+                None
+            };
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::GeohashBits;
+    use crate::Location;
+    use crate::Precision;
+    use std::collections::HashSet;
+
+    fn hashes<I: Iterator<Item = GeohashBits>>(iter: I) -> Vec<String> {
+        iter.map(|bits| bits.hash()).collect()
+    }
+
+    #[test]
+    fn test_disk() {
+        let center = GeohashBits::from_hash("u10hfr2c");
+        assert_eq!(hashes(center.disk(0)), vec!["u10hfr2c"]);
+        assert_eq!(
+            hashes(center.disk(1)),
+            vec![
+                "u10hfr28", "u10hfr2b", "u10hfr30", "u10hfr29", "u10hfr2c", "u10hfr31", "u10hfr2d",
+                "u10hfr2f", "u10hfr34",
+            ]
+        );
+        let disk: HashSet<String> = hashes(center.disk(3)).into_iter().collect();
+        assert_eq!(disk.len(), 49);
+    }
+
+    #[test]
+    fn test_ring() {
+        let center = GeohashBits::from_hash("u10hfr2c");
+        assert_eq!(hashes(center.ring(0)), vec!["u10hfr2c"]);
+        assert_eq!(
+            hashes(center.ring(1)),
+            vec![
+                "u10hfr28", "u10hfr2b", "u10hfr30", "u10hfr29", "u10hfr31", "u10hfr2d", "u10hfr2f",
+                "u10hfr34",
+            ]
+        );
+        let ring: HashSet<String> = hashes(center.ring(3)).into_iter().collect();
+        assert_eq!(ring.len(), 24);
+
+        let inner: HashSet<String> = hashes(center.disk(2)).into_iter().collect();
+        let outer: HashSet<String> = hashes(center.disk(3)).into_iter().collect();
+        assert_eq!(
+            outer.difference(&inner).cloned().collect::<HashSet<_>>(),
+            ring
+        );
+    }
+
+    #[test]
+    fn test_odd_characters() {
+        let center = GeohashBits::from_hash("gcpuv");
+        let disk = hashes(center.disk(1));
+        assert!(disk.iter().all(|hash| hash.len() == 5));
+        assert!(disk.contains(&"gcpvn".to_string()));
+        assert!(disk.contains(&"gcpus".to_string()));
+        assert_eq!(
+            hashes(center.ring(1)),
+            disk.into_iter()
+                .filter(|hash| hash != "gcpuv")
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_poles() {
+        let north = GeohashBits::from_location(
+            &Location {
+                longitude: 0.0,
+                latitude: 90.0,
+            },
+            Precision::Bits(10),
+        );
+        // only the row at the pole and the one below it
+        assert_eq!(north.disk(1).count(), 6);
+        assert_eq!(north.ring(2).count(), 5 + 2 + 2);
+        assert!(north
+            .disk(3)
+            .all(|bits| bits.bounding_box().min().latitude > 0.0));
+
+        let south = GeohashBits::from_hash("0000");
+        assert_eq!(south.disk(1).count(), 6);
+        assert!(south
+            .disk(3)
+            .all(|bits| bits.bounding_box().max().latitude < 0.0));
+    }
+
+    #[test]
+    fn test_antimeridian() {
+        let east_edge = GeohashBits::from_location(
+            &Location {
+                longitude: 179.99,
+                latitude: 0.0,
+            },
+            Precision::Characters(4),
+        );
+        let disk: Vec<GeohashBits> = east_edge.disk(1).collect();
+        assert_eq!(disk.len(), 9);
+        assert_eq!(
+            disk.iter()
+                .filter(|bits| bits.bounding_box().min().longitude < 0.0)
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_wraps_without_duplicates() {
+        // two columns by two rows covers the whole world
+        let cell = GeohashBits::from_location(
+            &Location {
+                longitude: 10.0,
+                latitude: 10.0,
+            },
+            Precision::Bits(1),
+        );
+        let disk: HashSet<u64> = cell.disk(1).map(|bits| bits.bits()).collect();
+        assert_eq!(cell.disk(1).count(), 4);
+        assert_eq!(disk.len(), 4);
+        assert_eq!(cell.ring(1).count(), 3);
+        assert_eq!(cell.ring(2).count(), 0);
+
+        let cell = GeohashBits::from_location(
+            &Location {
+                longitude: 10.0,
+                latitude: 10.0,
+            },
+            Precision::Bits(2),
+        );
+        assert_eq!(cell.disk(5).count(), 16);
+        let ring: Vec<u64> = cell.ring(2).map(|bits| bits.bits()).collect();
+        let unique: HashSet<&u64> = ring.iter().collect();
+        assert_eq!(ring.len(), unique.len());
+    }
+}
@@ -0,0 +1,420 @@
+// Interleaving of the latitude and longitude bits. BMI2 is used when the cpu
+// has a fast implementation of it, otherwise the portable version, which is
+// vectorized with SSE2 under the simd feature. The choice is made once, on
+// first use.
+use std::sync::OnceLock;
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+use self::scalar as portable;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+use self::sse2 as portable;
+
+pub fn interleave_bits(even_bits: u32, odd_bits: u32) -> u64 {
+    (implementation().interleave_bits)(even_bits, odd_bits)
+}
+
+pub fn deinterleave_bits(interleaved: u64) -> (u32, u32) {
+    (implementation().deinterleave_bits)(interleaved)
+}
+
+// Batch versions, the slices must all be the same length.
+pub fn interleave_slice(even_bits: &[u32], odd_bits: &[u32], interleaved: &mut [u64]) {
+    assert!(even_bits.len() == interleaved.len() && odd_bits.len() == interleaved.len());
+    (implementation().interleave_slice)(even_bits, odd_bits, interleaved)
+}
+
+pub fn deinterleave_slice(interleaved: &[u64], even_bits: &mut [u32], odd_bits: &mut [u32]) {
+    assert!(even_bits.len() == interleaved.len() && odd_bits.len() == interleaved.len());
+    (implementation().deinterleave_slice)(interleaved, even_bits, odd_bits)
+}
+
+struct Implementation {
+    interleave_bits: fn(u32, u32) -> u64,
+    deinterleave_bits: fn(u64) -> (u32, u32),
+    interleave_slice: fn(&[u32], &[u32], &mut [u64]),
+    deinterleave_slice: fn(&[u64], &mut [u32], &mut [u32]),
+}
+
+const PORTABLE: Implementation = Implementation {
+    interleave_bits: portable::interleave_bits,
+    deinterleave_bits: portable::deinterleave_bits,
+    interleave_slice: portable::interleave_slice,
+    deinterleave_slice: portable::deinterleave_slice,
+};
+
+fn implementation() -> &'static Implementation {
+    static IMPLEMENTATION: OnceLock<Implementation> = OnceLock::new();
+    IMPLEMENTATION.get_or_init(select)
+}
+
+#[cfg(target_arch = "x86_64")]
+fn select() -> Implementation {
+    if !bmi2::is_fast() {
+        return PORTABLE;
+    }
+    // is_fast has checked that the cpu supports bmi2
+    Implementation {
+        interleave_bits: |even_bits, odd_bits| unsafe {
+            bmi2::interleave_bits(even_bits, odd_bits)
+        },
+        deinterleave_bits: |interleaved| unsafe { bmi2::deinterleave_bits(interleaved) },
+        interleave_slice: |even_bits, odd_bits, interleaved| unsafe {
+            bmi2::interleave_slice(even_bits, odd_bits, interleaved)
+        },
+        deinterleave_slice: |interleaved, even_bits, odd_bits| unsafe {
+            bmi2::deinterleave_slice(interleaved, even_bits, odd_bits)
+        },
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn select() -> Implementation {
+    PORTABLE
+}
+
+// pdep/pext interleave in one instruction each. aarch64 has no equivalent
+// outside SVE2, it stays portable.
+#[cfg(target_arch = "x86_64")]
+mod bmi2 {
+    use std::arch::x86_64::{__cpuid, _pdep_u64, _pext_u64};
+
+    // AMD cpus before Zen 3 implement pdep and pext in microcode, taking
+    // hundreds of cycles, so they are better off with the portable version
+    pub fn is_fast() -> bool {
+        if !is_x86_feature_detected!("bmi2") {
+            return false;
+        }
+        #[allow(unused_unsafe)]
+        let (vendor, signature) = unsafe { (__cpuid(0), __cpuid(1).eax) };
+        let mut name = [0; 12];
+        name[..4].copy_from_slice(&vendor.ebx.to_le_bytes());
+        name[4..8].copy_from_slice(&vendor.edx.to_le_bytes());
+        name[8..].copy_from_slice(&vendor.ecx.to_le_bytes());
+        let mut family = (signature >> 8) & 0xf;
+        if family == 0xf {
+            family += (signature >> 20) & 0xff;
+        }
+        // Zen 3 is family 0x19, Hygon only has Zen 1 based cpus
+        !(&name == b"HygonGenuine" || (&name == b"AuthenticAMD" && family < 0x19))
+    }
+
+    // Safety: all of these require a cpu supporting bmi2, as checked by
+    // is_fast or is_x86_feature_detected!("bmi2").
+    #[target_feature(enable = "bmi2")]
+    pub unsafe fn interleave_bits(even_bits: u32, odd_bits: u32) -> u64 {
+        _pdep_u64(even_bits as u64, 0x5555555555555555)
+            | _pdep_u64(odd_bits as u64, 0xaaaaaaaaaaaaaaaa)
+    }
+
+    #[target_feature(enable = "bmi2")]
+    pub unsafe fn deinterleave_bits(interleaved: u64) -> (u32, u32) {
+        (
+            _pext_u64(interleaved, 0x5555555555555555) as u32,
+            _pext_u64(interleaved, 0xaaaaaaaaaaaaaaaa) as u32,
+        )
+    }
+
+    #[target_feature(enable = "bmi2")]
+    pub unsafe fn interleave_slice(even_bits: &[u32], odd_bits: &[u32], interleaved: &mut [u64]) {
+        for ((out, &even), &odd) in interleaved.iter_mut().zip(even_bits).zip(odd_bits) {
+            *out = interleave_bits(even, odd);
+        }
+    }
+
+    #[target_feature(enable = "bmi2")]
+    pub unsafe fn deinterleave_slice(
+        interleaved: &[u64],
+        even_bits: &mut [u32],
+        odd_bits: &mut [u32],
+    ) {
+        for ((&bits, even), odd) in interleaved.iter().zip(even_bits).zip(odd_bits) {
+            let (e, o) = deinterleave_bits(bits);
+            *even = e;
+            *odd = o;
+        }
+    }
+}
+
+// Spreads two values at once, one in each lane of a register. SSE2 is part
+// of the x86_64 baseline so it needs no runtime detection.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod sse2 {
+    use std::arch::x86_64::*;
+
+    const ALTERNATING_1: i64 = 0x5555555555555555;
+    const ALTERNATING_2: i64 = 0x3333333333333333;
+    const ALTERNATING_4: i64 = 0x0F0F0F0F0F0F0F0F;
+    const ALTERNATING_8: i64 = 0x00FF00FF00FF00FF;
+    const ALTERNATING_16: i64 = 0x0000FFFF0000FFFF;
+    const ALTERNATING_32: i64 = 0x00000000FFFFFFFF;
+
+    // safe since sse2 is always available on x86_64
+    pub fn interleave_bits(even_bits: u32, odd_bits: u32) -> u64 {
+        unsafe {
+            let bits = spread(_mm_set_epi64x(odd_bits as i64, even_bits as i64));
+            let (even, odd) = lanes(bits);
+            even | (odd << 1)
+        }
+    }
+
+    pub fn deinterleave_bits(interleaved: u64) -> (u32, u32) {
+        unsafe {
+            let bits = squash(_mm_and_si128(
+                _mm_set_epi64x((interleaved >> 1) as i64, interleaved as i64),
+                _mm_set1_epi64x(ALTERNATING_1),
+            ));
+            let (even, odd) = lanes(bits);
+            (even as u32, odd as u32)
+        }
+    }
+
+    pub fn interleave_slice(even_bits: &[u32], odd_bits: &[u32], interleaved: &mut [u64]) {
+        let pairs = interleaved.len() / 2;
+        for i in 0..pairs {
+            let (even, odd, out) = (&even_bits[2 * i..], &odd_bits[2 * i..], 2 * i);
+            unsafe {
+                let e = spread(_mm_set_epi64x(even[1] as i64, even[0] as i64));
+                let o = spread(_mm_set_epi64x(odd[1] as i64, odd[0] as i64));
+                let bits = _mm_or_si128(e, _mm_slli_epi64::<1>(o));
+                _mm_storeu_si128(interleaved[out..].as_mut_ptr() as *mut __m128i, bits);
+            }
+        }
+        for i in 2 * pairs..interleaved.len() {
+            interleaved[i] = interleave_bits(even_bits[i], odd_bits[i]);
+        }
+    }
+
+    pub fn deinterleave_slice(interleaved: &[u64], even_bits: &mut [u32], odd_bits: &mut [u32]) {
+        let pairs = interleaved.len() / 2;
+        for i in 0..pairs {
+            unsafe {
+                let bits = _mm_loadu_si128(interleaved[2 * i..].as_ptr() as *const __m128i);
+                let alternating = _mm_set1_epi64x(ALTERNATING_1);
+                let (e0, e1) = lanes(squash(_mm_and_si128(bits, alternating)));
+                let (o0, o1) = lanes(squash(_mm_and_si128(
+                    _mm_srli_epi64::<1>(bits),
+                    alternating,
+                )));
+                even_bits[2 * i] = e0 as u32;
+                even_bits[2 * i + 1] = e1 as u32;
+                odd_bits[2 * i] = o0 as u32;
+                odd_bits[2 * i + 1] = o1 as u32;
+            }
+        }
+        for i in 2 * pairs..interleaved.len() {
+            let (even, odd) = deinterleave_bits(interleaved[i]);
+            even_bits[i] = even;
+            odd_bits[i] = odd;
+        }
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn lanes(bits: __m128i) -> (u64, u64) {
+        (
+            _mm_cvtsi128_si64(bits) as u64,
+            _mm_cvtsi128_si64(_mm_unpackhi_epi64(bits, bits)) as u64,
+        )
+    }
+
+    // the low 32 bits of each lane moved to the even bits
+    #[target_feature(enable = "sse2")]
+    unsafe fn spread(mut bits: __m128i) -> __m128i {
+        bits = _mm_and_si128(
+            _mm_or_si128(bits, _mm_slli_epi64::<16>(bits)),
+            _mm_set1_epi64x(ALTERNATING_16),
+        );
+        bits = _mm_and_si128(
+            _mm_or_si128(bits, _mm_slli_epi64::<8>(bits)),
+            _mm_set1_epi64x(ALTERNATING_8),
+        );
+        bits = _mm_and_si128(
+            _mm_or_si128(bits, _mm_slli_epi64::<4>(bits)),
+            _mm_set1_epi64x(ALTERNATING_4),
+        );
+        bits = _mm_and_si128(
+            _mm_or_si128(bits, _mm_slli_epi64::<2>(bits)),
+            _mm_set1_epi64x(ALTERNATING_2),
+        );
+        _mm_and_si128(
+            _mm_or_si128(bits, _mm_slli_epi64::<1>(bits)),
+            _mm_set1_epi64x(ALTERNATING_1),
+        )
+    }
+
+    // the inverse of spread, the odd bits must already be clear
+    #[target_feature(enable = "sse2")]
+    unsafe fn squash(mut bits: __m128i) -> __m128i {
+        bits = _mm_and_si128(
+            _mm_or_si128(bits, _mm_srli_epi64::<1>(bits)),
+            _mm_set1_epi64x(ALTERNATING_2),
+        );
+        bits = _mm_and_si128(
+            _mm_or_si128(bits, _mm_srli_epi64::<2>(bits)),
+            _mm_set1_epi64x(ALTERNATING_4),
+        );
+        bits = _mm_and_si128(
+            _mm_or_si128(bits, _mm_srli_epi64::<4>(bits)),
+            _mm_set1_epi64x(ALTERNATING_8),
+        );
+        bits = _mm_and_si128(
+            _mm_or_si128(bits, _mm_srli_epi64::<8>(bits)),
+            _mm_set1_epi64x(ALTERNATING_16),
+        );
+        _mm_and_si128(
+            _mm_or_si128(bits, _mm_srli_epi64::<16>(bits)),
+            _mm_set1_epi64x(ALTERNATING_32),
+        )
+    }
+}
+
+// the reference the other versions are tested against
+#[cfg(any(test, not(all(feature = "simd", target_arch = "x86_64"))))]
+mod scalar {
+    pub fn interleave_bits(even_bits: u32, odd_bits: u32) -> u64 {
+        let mut e = even_bits as u64;
+        let mut o = odd_bits as u64;
+
+        e = (e | (e << 16)) & 0x0000FFFF0000FFFF;
+        o = (o | (o << 16)) & 0x0000FFFF0000FFFF;
+
+        e = (e | (e << 8)) & 0x00FF00FF00FF00FF;
+        o = (o | (o << 8)) & 0x00FF00FF00FF00FF;
+
+        e = (e | (e << 4)) & 0x0F0F0F0F0F0F0F0F;
+        o = (o | (o << 4)) & 0x0F0F0F0F0F0F0F0F;
+
+        e = (e | (e << 2)) & 0x3333333333333333;
+        o = (o | (o << 2)) & 0x3333333333333333;
+
+        e = (e | (e << 1)) & 0x5555555555555555;
+        o = (o | (o << 1)) & 0x5555555555555555;
+
+        e | (o << 1)
+    }
+
+    pub fn deinterleave_bits(interleaved: u64) -> (u32, u32) {
+        let mut e = interleaved & 0x5555555555555555;
+        let mut o = (interleaved >> 1) & 0x5555555555555555;
+
+        e = (e | (e >> 1)) & 0x3333333333333333;
+        o = (o | (o >> 1)) & 0x3333333333333333;
+
+        e = (e | (e >> 2)) & 0x0F0F0F0F0F0F0F0F;
+        o = (o | (o >> 2)) & 0x0F0F0F0F0F0F0F0F;
+
+        e = (e | (e >> 4)) & 0x00FF00FF00FF00FF;
+        o = (o | (o >> 4)) & 0x00FF00FF00FF00FF;
+
+        e = (e | (e >> 8)) & 0x0000FFFF0000FFFF;
+        o = (o | (o >> 8)) & 0x0000FFFF0000FFFF;
+
+        e = (e | (e >> 16)) & 0x00000000FFFFFFFF;
+        o = (o | (o >> 16)) & 0x00000000FFFFFFFF;
+
+        (e as u32, o as u32)
+    }
+
+    // simple enough loops for the compiler to vectorize
+    pub fn interleave_slice(even_bits: &[u32], odd_bits: &[u32], interleaved: &mut [u64]) {
+        for ((out, &even), &odd) in interleaved.iter_mut().zip(even_bits).zip(odd_bits) {
+            *out = interleave_bits(even, odd);
+        }
+    }
+
+    pub fn deinterleave_slice(interleaved: &[u64], even_bits: &mut [u32], odd_bits: &mut [u32]) {
+        for ((&bits, even), odd) in interleaved.iter().zip(even_bits).zip(odd_bits) {
+            let (e, o) = deinterleave_bits(bits);
+            *even = e;
+            *odd = o;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn values() -> impl Iterator<Item = (u32, u32)> {
+        let mut value: u64 = 0x9e3779b97f4a7c15;
+        std::iter::repeat_with(move || {
+            value = value
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (value as u32, (value >> 32) as u32)
+        })
+        .take(1000)
+        .chain(vec![
+            (0, 0),
+            (u32::MAX, 0),
+            (0, u32::MAX),
+            (u32::MAX, u32::MAX),
+        ])
+    }
+
+    #[test]
+    fn test_implementations_agree() {
+        for (even, odd) in values() {
+            let interleaved = super::scalar::interleave_bits(even, odd);
+            assert_eq!(super::scalar::deinterleave_bits(interleaved), (even, odd));
+            assert_eq!(super::interleave_bits(even, odd), interleaved);
+            assert_eq!(super::deinterleave_bits(interleaved), (even, odd));
+            assert_eq!(super::portable::interleave_bits(even, odd), interleaved);
+            assert_eq!(super::portable::deinterleave_bits(interleaved), (even, odd));
+            #[cfg(target_arch = "x86_64")]
+            {
+                if is_x86_feature_detected!("bmi2") {
+                    unsafe {
+                        assert_eq!(super::bmi2::interleave_bits(even, odd), interleaved);
+                        assert_eq!(super::bmi2::deinterleave_bits(interleaved), (even, odd));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_slices_agree() {
+        let (even, odd): (Vec<u32>, Vec<u32>) = values().unzip();
+        let expected: Vec<u64> = values()
+            .map(|(e, o)| super::scalar::interleave_bits(e, o))
+            .collect();
+        // odd lengths exercise the remainder of the paired loops
+        for &len in &[0, 1, 2, 7, expected.len()] {
+            let mut interleaved = vec![0; len];
+            super::interleave_slice(&even[..len], &odd[..len], &mut interleaved);
+            assert_eq!(interleaved, &expected[..len]);
+            super::portable::interleave_slice(&even[..len], &odd[..len], &mut interleaved);
+            assert_eq!(interleaved, &expected[..len]);
+            super::scalar::interleave_slice(&even[..len], &odd[..len], &mut interleaved);
+            assert_eq!(interleaved, &expected[..len]);
+
+            let (mut e, mut o) = (vec![0; len], vec![0; len]);
+            super::deinterleave_slice(&interleaved, &mut e, &mut o);
+            assert_eq!((&e[..], &o[..]), (&even[..len], &odd[..len]));
+            let (mut e, mut o) = (vec![0; len], vec![0; len]);
+            super::portable::deinterleave_slice(&interleaved, &mut e, &mut o);
+            assert_eq!((&e[..], &o[..]), (&even[..len], &odd[..len]));
+            let (mut e, mut o) = (vec![0; len], vec![0; len]);
+            super::scalar::deinterleave_slice(&interleaved, &mut e, &mut o);
+            assert_eq!((&e[..], &o[..]), (&even[..len], &odd[..len]));
+            #[cfg(target_arch = "x86_64")]
+            {
+                if is_x86_feature_detected!("bmi2") {
+                    unsafe {
+                        super::bmi2::interleave_slice(&even[..len], &odd[..len], &mut interleaved)
+                    };
+                    assert_eq!(interleaved, &expected[..len]);
+                    let (mut e, mut o) = (vec![0; len], vec![0; len]);
+                    unsafe { super::bmi2::deinterleave_slice(&interleaved, &mut e, &mut o) };
+                    assert_eq!((&e[..], &o[..]), (&even[..len], &odd[..len]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_interleave_bits() {
+        assert_eq!(super::interleave_bits(0b11, 0), 0b0101);
+        assert_eq!(super::interleave_bits(0, 0b11), 0b1010);
+        assert_eq!(super::interleave_bits(0b10, 0b01), 0b0110);
+        assert_eq!(super::deinterleave_bits(0b0110), (0b10, 0b01));
+    }
+}
@@ -0,0 +1,201 @@
+// Parallel versions of batch encoding, iteration and covering, enabled with
+// the `rayon` feature. Each produces exactly what its sequential version does.
+use crate::batch::encode_locations;
+use crate::cover::{children_within, next_level};
+use crate::*;
+use rayon::prelude::*;
+
+// large enough that a task outweighs scheduling it
+const PAR_CHUNK: usize = 1 << 14;
+
+pub fn par_encode_batch(
+    locations: &[Location],
+    precision: Precision,
+    out: &mut [u64],
+) -> Result<(), GeohashError> {
+    assert_eq!(locations.len(), out.len(), "slices differ in length");
+    precision.validate()?;
+    // report the first invalid location, like encode_batch
+    if let Some(error) = locations
+        .par_iter()
+        .filter_map(|location| location.validate().err())
+        .find_first(|_| true)
+    {
+        return Err(error);
+    }
+    locations
+        .par_chunks(PAR_CHUNK)
+        .zip(out.par_chunks_mut(PAR_CHUNK))
+        .for_each(|(locations, out)| encode_locations(locations, precision, out));
+    Ok(())
+}
+
+// The cells of GeohashIterator::with_precision, split by latitude rows.
+pub fn par_geohash_iter(
+    bounds: BoundingBox,
+    precision: Precision,
+) -> impl ParallelIterator<Item = GeohashBits> {
+    GeohashIterator::row_starts(bounds, precision)
+        .into_par_iter()
+        .flat_map_iter(move |start| GeohashIterator::row(bounds, start))
+}
+
+pub fn par_cover_region<R: Region + Sync>(
+    region: &R,
+    precision: Precision,
+) -> Vec<(GeohashBits, Coverage)> {
+    if let Err(error) = precision.validate() {
+        panic!("{}", error);
+    }
+    par_cover_cell(region, GeohashBits::world(), precision)
+}
+
+fn par_cover_cell<R: Region + Sync>(
+    region: &R,
+    cell: GeohashBits,
+    precision: Precision,
+) -> Vec<(GeohashBits, Coverage)> {
+    match region.coverage(&cell.bounding_box()) {
+        None => Vec::new(),
+        Some(Coverage::Full) => cell
+            .descendants(precision)
+            .map(|bits| (bits, Coverage::Full))
+            .collect(),
+        Some(Coverage::Partial) => {
+            if cell.precision().significant_bits() == precision.significant_bits() {
+                vec![(cell, Coverage::Partial)]
+            } else {
+                let children: Vec<GeohashBits> = cell
+                    .descendants(next_level(cell.precision(), precision))
+                    .collect();
+                children
+                    .into_par_iter()
+                    .map(|child| par_cover_cell(region, child, precision))
+                    .collect::<Vec<_>>()
+                    .concat()
+            }
+        }
+    }
+}
+
+impl CoverBuilder {
+    pub fn par_cover<R: Region + Sync>(&self, region: &R) -> Vec<GeohashBits> {
+        self.validate();
+        // The first call of a level computes the children of the rest of it.
+        // Every candidate after level[i] leaves at most one more cell for its
+        // children than level[i] does, which bounds their limits.
+        let mut computed: Vec<Option<Vec<(GeohashBits, Coverage)>>> = Vec::new();
+        let mut computed_level = None;
+        self.refine(
+            |precision| par_cover_region(region, precision),
+            |level, i, limit| {
+                if computed_level != Some(level[0]) {
+                    computed_level = Some(level[0]);
+                    computed = level
+                        .par_iter()
+                        .enumerate()
+                        .map(|(j, bits)| {
+                            if j < i {
+                                None
+                            } else {
+                                children_within(region, bits, limit.saturating_add(j - i))
+                            }
+                        })
+                        .collect();
+                }
+                computed[i]
+                    .take()
+                    .filter(|children| children.len() <= limit)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cover_region, encode_batch, par_cover_region, par_encode_batch, par_geohash_iter};
+    use crate::{BoundingBox, CoverBuilder, GeohashBits, GeohashError, GeohashIterator};
+    use crate::{Location, Polygon, Precision};
+    use rayon::prelude::*;
+
+    fn triangle() -> Polygon {
+        Polygon::new(
+            vec![
+                Location {
+                    longitude: -10.0,
+                    latitude: 35.0,
+                },
+                Location {
+                    longitude: 30.0,
+                    latitude: 40.0,
+                },
+                Location {
+                    longitude: 5.0,
+                    latitude: 60.0,
+                },
+            ],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_par_encode_batch() {
+        let mut locations: Vec<Location> = (0..50_000)
+            .map(|i| Location {
+                longitude: -180.0 + 0.0072 * i as f64,
+                latitude: -90.0 + 0.0036 * i as f64,
+            })
+            .collect();
+        let mut expected = vec![0; locations.len()];
+        let mut bits = vec![0; locations.len()];
+        encode_batch(&locations, Precision::Characters(9), &mut expected).unwrap();
+        par_encode_batch(&locations, Precision::Characters(9), &mut bits).unwrap();
+        assert_eq!(bits, expected);
+
+        locations[40_000].longitude = 200.0;
+        locations[45_000].latitude = 100.0;
+        assert_eq!(
+            par_encode_batch(&locations, Precision::Characters(9), &mut bits),
+            Err(GeohashError::LongitudeOutOfRange(200.0))
+        );
+    }
+
+    #[test]
+    fn test_par_geohash_iter() {
+        let bounds = GeohashBits::from_hash("dp3").bounding_box();
+        for &precision in &[Precision::Bits(12), Precision::Characters(5)] {
+            let expected: Vec<GeohashBits> =
+                GeohashIterator::with_precision(bounds, precision).collect();
+            let cells: Vec<GeohashBits> = par_geohash_iter(bounds, precision).collect();
+            assert_eq!(cells, expected);
+        }
+
+        let world = BoundingBox::new(
+            &Location {
+                longitude: -180.0,
+                latitude: -90.0,
+            },
+            &Location {
+                longitude: 180.0,
+                latitude: 90.0,
+            },
+        );
+        assert_eq!(par_geohash_iter(world, Precision::Bits(3)).count(), 64);
+    }
+
+    #[test]
+    fn test_par_cover() {
+        let polygon = triangle();
+        assert_eq!(
+            par_cover_region(&polygon, Precision::Bits(10)),
+            cover_region(&polygon, Precision::Bits(10))
+        );
+        for &max_cells in &[8, 50, 1000] {
+            let builder = CoverBuilder::new()
+                .min_precision(Precision::Characters(1))
+                .max_precision(Precision::Characters(5))
+                .max_cells(max_cells);
+            assert_eq!(builder.par_cover(&polygon), builder.cover(&polygon));
+        }
+    }
+}
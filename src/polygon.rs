@@ -0,0 +1,186 @@
+use crate::*;
+
+// A polygon with straight edges in latitude/longitude space. Rings are
+// implicitly closed, repeating the first point at the end is optional.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polygon {
+    exterior: Vec<Location>,
+    holes: Vec<Vec<Location>>,
+    bounds: BoundingBox,
+}
+
+impl Polygon {
+    pub fn new(exterior: Vec<Location>, holes: Vec<Vec<Location>>) -> Polygon {
+        match Polygon::try_new(exterior, holes) {
+            Ok(polygon) => polygon,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    pub fn try_new(
+        exterior: Vec<Location>,
+        holes: Vec<Vec<Location>>,
+    ) -> Result<Polygon, GeohashError> {
+        for location in exterior.iter().chain(holes.iter().flatten()) {
+            location.validate()?;
+        }
+        let bounds = match BoundingBox::enclosing(exterior.iter().cloned()) {
+            Some(bounds) => bounds,
+            None => return Err(GeohashError::EmptyPolygon),
+        };
+        Ok(Polygon {
+            exterior,
+            holes,
+            bounds,
+        })
+    }
+
+    pub fn exterior(&self) -> &[Location] {
+        &self.exterior
+    }
+
+    pub fn holes(&self) -> &[Vec<Location>] {
+        &self.holes
+    }
+
+    pub fn bounding_box(&self) -> &BoundingBox {
+        &self.bounds
+    }
+
+    pub fn contains(&self, location: &Location) -> bool {
+        edges_contain(self.edges(), location)
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Location, Location)> + '_ {
+        std::iter::once(&self.exterior)
+            .chain(self.holes.iter())
+            .flat_map(|ring| {
+                ring.iter()
+                    .cloned()
+                    .zip(ring.iter().cloned().cycle().skip(1))
+            })
+    }
+}
+
+impl Region for Polygon {
+    fn coverage(&self, cell: &BoundingBox) -> Option<Coverage> {
+        if !self.bounds.intersects(cell) {
+            return None;
+        }
+        edges_coverage(|| self.edges(), cell)
+    }
+}
+
+// even-odd rule, so points inside a hole are outside the polygon
+pub(crate) fn edges_contain<I>(edges: I, location: &Location) -> bool
+where
+    I: Iterator<Item = (Location, Location)>,
+{
+    let mut inside = false;
+    for (a, b) in edges {
+        if (a.latitude > location.latitude) != (b.latitude > location.latitude) {
+            let crossing = a.longitude
+                + (location.latitude - a.latitude) * (b.longitude - a.longitude)
+                    / (b.latitude - a.latitude);
+            if location.longitude < crossing {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+pub(crate) fn edges_coverage<F, I>(edges: F, cell: &BoundingBox) -> Option<Coverage>
+where
+    F: Fn() -> I,
+    I: Iterator<Item = (Location, Location)>,
+{
+    if edges().any(|(a, b)| segment_intersects(&a, &b, cell)) {
+        return Some(Coverage::Partial);
+    }
+    // no edge enters the cell so it is either entirely inside or outside
+    if edges_contain(edges(), &cell.center()) {
+        Some(Coverage::Full)
+    } else {
+        None
+    }
+}
+
+// Liang-Barsky clipping of the segment against the box
+fn segment_intersects(a: &Location, b: &Location, bounds: &BoundingBox) -> bool {
+    let dx = b.longitude - a.longitude;
+    let dy = b.latitude - a.latitude;
+    let mut t0: f64 = 0.0;
+    let mut t1: f64 = 1.0;
+    for &(p, q) in &[
+        (-dx, a.longitude - bounds.min().longitude),
+        (dx, bounds.max().longitude - a.longitude),
+        (-dy, a.latitude - bounds.min().latitude),
+        (dy, bounds.max().latitude - a.latitude),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return false;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                if t > t1 {
+                    return false;
+                }
+                t0 = t0.max(t);
+            } else {
+                if t < t0 {
+                    return false;
+                }
+                t1 = t1.min(t);
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{GeohashError, Location, Polygon};
+
+    fn location(longitude: f64, latitude: f64) -> Location {
+        Location {
+            longitude,
+            latitude,
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        let polygon = Polygon::new(
+            vec![
+                location(0.0, 0.0),
+                location(4.0, 0.0),
+                location(4.0, 4.0),
+                location(0.0, 4.0),
+            ],
+            vec![vec![
+                location(1.0, 1.0),
+                location(2.0, 1.0),
+                location(2.0, 2.0),
+                location(1.0, 2.0),
+                location(1.0, 1.0),
+            ]],
+        );
+        assert!(polygon.contains(&location(3.0, 3.0)));
+        assert!(polygon.contains(&location(0.5, 1.5)));
+        assert!(!polygon.contains(&location(1.5, 1.5)));
+        assert!(!polygon.contains(&location(5.0, 1.5)));
+        assert!(!polygon.contains(&location(-0.5, 3.0)));
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(Polygon::try_new(vec![location(0.0, 0.0), location(200.0, 1.0)], vec![]).is_err());
+        assert_eq!(
+            Polygon::try_new(vec![], vec![]).err(),
+            Some(GeohashError::EmptyPolygon)
+        );
+    }
+}
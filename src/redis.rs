@@ -0,0 +1,129 @@
+// Compatibility with the Redis GEO commands. Redis stores locations as
+// 52-bit sorted set scores, 26 bits per axis with latitude limited to the
+// Web Mercator range, and GEOHASH reports them as standard 11 character
+// hashes whose last character is always '0'.
+use crate::geohash_bits::{bits_to_float, float_to_bits};
+use crate::*;
+
+pub const SCORE_PRECISION: Precision = Precision::Bits(26);
+pub const MAX_LATITUDE: f64 = 85.05112878;
+const SCORE_LATITUDE_RANGE: LocationRange = -MAX_LATITUDE..=MAX_LATITUDE;
+const SCORE_BITS: u32 = 52;
+
+// Like GEOADD. Redis lets a coordinate at the end of its range spill into
+// the next bit, here it stays in the last cell like everywhere else.
+pub fn encode_score(location: &Location) -> Result<u64, GeohashError> {
+    location.validate()?;
+    if !SCORE_LATITUDE_RANGE.contains(&location.latitude) {
+        return Err(GeohashError::LatitudeOutOfRange(location.latitude));
+    }
+    let max_binary_value = SCORE_PRECISION.max_binary_value();
+    Ok(ops::interleave_bits(
+        float_to_bits(location.latitude, &SCORE_LATITUDE_RANGE, max_binary_value),
+        float_to_bits(location.longitude, &LONGITUDE_RANGE, max_binary_value),
+    ))
+}
+
+// Like GEOPOS, the center of the score's cell.
+pub fn decode_score(score: u64) -> Result<Location, GeohashError> {
+    if score >> SCORE_BITS != 0 {
+        return Err(GeohashError::BitsOutOfRange(score));
+    }
+    let max_binary_value = SCORE_PRECISION.max_binary_value();
+    let (lat_bits, lon_bits) = ops::deinterleave_bits(score);
+    let center = |bits: u32, range: &LocationRange| {
+        0.5 * (bits_to_float(bits, range, max_binary_value)
+            + bits_to_float(bits + 1, range, max_binary_value))
+    };
+    Ok(Location {
+        longitude: center(lon_bits, &LONGITUDE_RANGE).clamp(-180.0, 180.0),
+        latitude: center(lat_bits, &SCORE_LATITUDE_RANGE).clamp(-MAX_LATITUDE, MAX_LATITUDE),
+    })
+}
+
+// Like GEOHASH, which re-encodes the decoded score with the standard
+// latitude range and pads the 50 bits of 10 characters with a '0'.
+pub fn geohash(score: u64) -> Result<GeohashStr, GeohashError> {
+    let location = decode_score(score)?;
+    let mut hash = GeohashBits::from_location(&location, SCORE_PRECISION).hash_inline();
+    hash.push(b'0');
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::redis::{decode_score, encode_score, geohash};
+    use crate::{GeohashError, Location};
+    use assert_approx_eq::assert_approx_eq;
+
+    // GEOADD Sicily 13.361389 38.115556 "Palermo" 15.087269 37.502669 "Catania"
+    // followed by ZSCORE, GEOPOS and GEOHASH
+    const SICILY: [(f64, f64, u64, f64, f64, &str); 2] = [
+        (
+            13.361389,
+            38.115556,
+            3479099956230698,
+            13.361_389_338_970_184,
+            38.115_556_395_496_3,
+            "sqc8b49rny0",
+        ),
+        (
+            15.087269,
+            37.502669,
+            3479447370796909,
+            15.087_267_458_438_873,
+            37.502_668_423_331_62,
+            "sqdtr74hyu0",
+        ),
+    ];
+
+    #[test]
+    fn test_sicily() {
+        for &(longitude, latitude, score, geopos_longitude, geopos_latitude, hash) in &SICILY {
+            let location = Location {
+                longitude,
+                latitude,
+            };
+            assert_eq!(encode_score(&location), Ok(score));
+            let position = decode_score(score).unwrap();
+            assert_approx_eq!(position.longitude, geopos_longitude, 1e-12);
+            assert_approx_eq!(position.latitude, geopos_latitude, 1e-12);
+            assert_eq!(geohash(score).unwrap(), hash);
+        }
+    }
+
+    #[test]
+    fn test_geohash() {
+        let score = encode_score(&Location {
+            longitude: -0.1,
+            latitude: 51.5,
+        })
+        .unwrap();
+        assert_eq!(geohash(score).unwrap(), "gcpuvxr1jz0");
+    }
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(
+            encode_score(&Location {
+                longitude: 0.0,
+                latitude: 86.0
+            }),
+            Err(GeohashError::LatitudeOutOfRange(86.0))
+        );
+        assert_eq!(
+            decode_score(1 << 52),
+            Err(GeohashError::BitsOutOfRange(1 << 52))
+        );
+
+        let corner = Location {
+            longitude: 180.0,
+            latitude: 85.05112878,
+        };
+        let score = encode_score(&corner).unwrap();
+        assert_eq!(score, (1 << 52) - 1);
+        let position = decode_score(score).unwrap();
+        assert!(position.longitude < 180.0 && position.latitude < 85.05112878);
+        assert_eq!(encode_score(&position), Ok(score));
+    }
+}
@@ -0,0 +1,226 @@
+// Serde support, enabled with the `serde` feature. Deserialization goes
+// through the fallible constructors so invalid values are rejected rather
+// than constructed.
+//
+// GeohashBits serialize as `{bits, precision}` by default, annotate a field
+// with `#[serde(with = "geohash::serialization::hash")]` to use the base32
+// string instead. The string form only serializes Precision::Characters, as
+// a hash always deserializes to characters.
+use crate::geohash_bits::{MAX_BINARY_PRECISION, MAX_CHARACTER_PRECISION};
+use crate::*;
+use serde::Deserialize;
+use std::convert::TryFrom;
+
+#[derive(Deserialize)]
+#[serde(rename = "Location")]
+pub(crate) struct RawLocation {
+    longitude: f64,
+    latitude: f64,
+}
+
+impl TryFrom<RawLocation> for Location {
+    type Error = GeohashError;
+
+    fn try_from(raw: RawLocation) -> Result<Location, GeohashError> {
+        Location::try_new(raw.longitude, raw.latitude)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "BoundingBox")]
+pub(crate) struct RawBoundingBox {
+    min: Location,
+    max: Location,
+}
+
+impl TryFrom<RawBoundingBox> for BoundingBox {
+    type Error = GeohashError;
+
+    fn try_from(raw: RawBoundingBox) -> Result<BoundingBox, GeohashError> {
+        BoundingBox::try_new(&raw.min, &raw.max)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Precision")]
+pub(crate) enum RawPrecision {
+    Bits(u8),
+    Characters(u8),
+}
+
+impl TryFrom<RawPrecision> for Precision {
+    type Error = GeohashError;
+
+    fn try_from(raw: RawPrecision) -> Result<Precision, GeohashError> {
+        // check the count itself, bit counts of untrusted values could overflow
+        let (precision, in_range) = match raw {
+            RawPrecision::Bits(n) => (Precision::Bits(n), (1..=MAX_BINARY_PRECISION).contains(&n)),
+            RawPrecision::Characters(n) => (
+                Precision::Characters(n),
+                (1..=MAX_CHARACTER_PRECISION as u8).contains(&n),
+            ),
+        };
+        if !in_range {
+            return Err(GeohashError::PrecisionOutOfRange(
+                precision.binary_precision(),
+            ));
+        }
+        Ok(precision)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "GeohashBits")]
+pub(crate) struct RawGeohashBits {
+    bits: u64,
+    precision: Precision,
+}
+
+impl TryFrom<RawGeohashBits> for GeohashBits {
+    type Error = GeohashError;
+
+    fn try_from(raw: RawGeohashBits) -> Result<GeohashBits, GeohashError> {
+        GeohashBits::try_from_bits(raw.bits, raw.precision)
+    }
+}
+
+pub mod hash {
+    use crate::{GeohashBits, Precision};
+    use serde::de::Error as _;
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    // Precision::Bits are rejected even when they fill whole characters, the
+    // hash would come back as Precision::Characters and compare unequal
+    pub fn serialize<S: Serializer>(bits: &GeohashBits, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Precision::Bits(_) = bits.precision() {
+            return Err(S::Error::custom(format!(
+                "{} has no base32 representation",
+                bits.precision()
+            )));
+        }
+        serializer.serialize_str(&bits.hash_inline())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<GeohashBits, D::Error> {
+        let hash = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        GeohashBits::try_from_hash(&hash).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BoundingBox, GeohashBits, Location, Precision};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Hashed {
+        #[serde(with = "crate::serialization::hash")]
+        cell: GeohashBits,
+    }
+
+    #[test]
+    fn test_location() {
+        let location = Location {
+            longitude: -0.1,
+            latitude: 51.5,
+        };
+        let json = serde_json::to_string(&location).unwrap();
+        assert_eq!(json, r#"{"longitude":-0.1,"latitude":51.5}"#);
+        assert_eq!(serde_json::from_str::<Location>(&json).unwrap(), location);
+        assert!(serde_json::from_str::<Location>(r#"{"longitude":181.0,"latitude":0.0}"#).is_err());
+        assert!(serde_json::from_str::<Location>(r#"{"longitude":0.0,"latitude":-91.0}"#).is_err());
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bbox = GeohashBits::from_hash("gcpuv").bounding_box();
+        let json = serde_json::to_string(&bbox).unwrap();
+        assert_eq!(serde_json::from_str::<BoundingBox>(&json).unwrap(), bbox);
+        let error = serde_json::from_str::<BoundingBox>(
+            r#"{"min":{"longitude":0.0,"latitude":1.0},"max":{"longitude":1.0,"latitude":0.0}}"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("latitude out of range: 1"));
+    }
+
+    #[test]
+    fn test_precision() {
+        let json = serde_json::to_string(&Precision::Characters(5)).unwrap();
+        assert_eq!(json, r#"{"Characters":5}"#);
+        assert_eq!(
+            serde_json::from_str::<Precision>(&json).unwrap(),
+            Precision::Characters(5)
+        );
+        assert!(serde_json::from_str::<Precision>(r#"{"Bits":0}"#).is_err());
+        assert!(serde_json::from_str::<Precision>(r#"{"Characters":13}"#).is_err());
+    }
+
+    #[test]
+    fn test_precision_out_of_range() {
+        // large enough to overflow the bit counts
+        for json in &[
+            r#"{"Characters":60}"#,
+            r#"{"Characters":255}"#,
+            r#"{"Bits":33}"#,
+            r#"{"Bits":200}"#,
+        ] {
+            let error = serde_json::from_str::<Precision>(json).unwrap_err();
+            assert!(error.to_string().contains("precision out of range"));
+            let bits = format!(r#"{{"bits":0,"precision":{}}}"#, json);
+            assert!(serde_json::from_str::<GeohashBits>(&bits).is_err());
+        }
+    }
+
+    #[test]
+    fn test_geohash_bits() {
+        let bits = GeohashBits::from_hash("gcpuv");
+        let json = serde_json::to_string(&bits).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"bits":{},"precision":{{"Characters":5}}}}"#,
+                bits.bits()
+            )
+        );
+        assert_eq!(serde_json::from_str::<GeohashBits>(&json).unwrap(), bits);
+        // the padding bit of an odd character count must be clear
+        let padded = format!(
+            r#"{{"bits":{},"precision":{{"Characters":5}}}}"#,
+            bits.bits() | 1
+        );
+        assert!(serde_json::from_str::<GeohashBits>(&padded).is_err());
+    }
+
+    #[test]
+    fn test_hash() {
+        let hashed = Hashed {
+            cell: GeohashBits::from_hash("gcpuv"),
+        };
+        let json = serde_json::to_string(&hashed).unwrap();
+        assert_eq!(json, r#"{"cell":"gcpuv"}"#);
+        assert_eq!(serde_json::from_str::<Hashed>(&json).unwrap(), hashed);
+        assert!(serde_json::from_str::<Hashed>(r#"{"cell":"gcpua"}"#).is_err());
+        assert!(serde_json::from_str::<Hashed>(r#"{"cell":""}"#).is_err());
+
+        let bits = Hashed {
+            cell: GeohashBits::from_location(
+                &Location {
+                    longitude: -0.1,
+                    latitude: 51.5,
+                },
+                Precision::Bits(16),
+            ),
+        };
+        assert!(serde_json::to_string(&bits).is_err());
+
+        // 20 bits fill 4 characters but would come back as Characters(4)
+        let bits = Hashed {
+            cell: GeohashBits::from_hash("gcpu").with_precision(Precision::Bits(10)),
+        };
+        assert_eq!(bits.cell.hash(), "gcpu");
+        assert!(serde_json::to_string(&bits).is_err());
+    }
+}